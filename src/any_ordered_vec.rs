@@ -0,0 +1,80 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::{invariant::InvariantError, metrics::Metrics, simple::OrderedVec, utils::Id};
+
+/// An ordered vector that can store elements of any type, and downcast them back when we fetch them
+/// This is a safe alternative to type erased storage, at the cost of a box per element
+#[derive(Default)]
+pub struct AnyOrderedVec {
+    /// The underlying ordered vector containing the boxed elements
+    pub(crate) vec: OrderedVec<Box<dyn Any + Send>>,
+}
+
+impl Debug for AnyOrderedVec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnyOrderedVec")
+            .field("count", &self.vec.count())
+            .field("missing", &self.vec.slots.missing)
+            .finish()
+    }
+}
+
+impl AnyOrderedVec {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add an element of any type to the ordered vector
+    pub fn insert_any<T: Any + Send>(&mut self, elem: T) -> Id {
+        self.vec.push_shove(Box::new(elem))
+    }
+    /// Add an already boxed element to the ordered vector
+    pub fn insert_boxed(&mut self, elem: Box<dyn Any + Send>) -> Id {
+        self.vec.push_shove(elem)
+    }
+    /// Get a reference to an element, without downcasting it
+    pub fn get(&self, id: Id) -> Option<&(dyn Any + Send)> {
+        self.vec.get(id).map(|x| x.as_ref())
+    }
+    /// Get a reference to an element, but only if it is of type T
+    pub fn get_downcast<T: Any>(&self, id: Id) -> Option<&T> {
+        self.vec.get(id)?.downcast_ref::<T>()
+    }
+    /// Get a mutable reference to an element, but only if it is of type T
+    pub fn get_downcast_mut<T: Any>(&mut self, id: Id) -> Option<&mut T> {
+        self.vec.get_mut(id)?.downcast_mut::<T>()
+    }
+    /// Check if the element with the given ID is of type T
+    pub fn is<T: Any>(&self, id: Id) -> bool {
+        self.vec.get(id).map(|x| x.is::<T>()).unwrap_or_default()
+    }
+    /// Remove an element that is contained in the vec, without downcasting it
+    pub fn remove(&mut self, id: Id) -> Option<Box<dyn Any + Send>> {
+        self.vec.remove(id)
+    }
+    /// Remove an element that is contained in the vec, but only if it is of type T. Otherwise the element is left untouched
+    pub fn remove_downcast<T: Any>(&mut self, id: Id) -> Option<T> {
+        if !self.is::<T>(id) {
+            return None;
+        }
+        self.vec.remove(id)?.downcast::<T>().ok().map(|x| *x)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        self.vec.metrics()
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.vec.count()
+    }
+    /// Get an iterator over the elements of type T, with the ID of each element
+    pub fn iter_downcast<T: Any>(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.vec
+            .iter()
+            .filter_map(|(id, elem)| elem.downcast_ref::<T>().map(|elem| (id, elem)))
+    }
+}
@@ -0,0 +1,212 @@
+use std::ops;
+
+use crate::{invariant::InvariantError, metrics::Metrics, ordered_vec::OrderedVec, utils::Id};
+
+/// The index of an element inside an Arena, containing its slot and generation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Index(Id);
+
+impl Index {
+    /// Create an index from its slot and generation
+    pub fn from_raw_parts(a: usize, b: u64) -> Self {
+        Self(Id::new(a, b as u32))
+    }
+    /// Get the slot and generation of this index
+    pub fn into_raw_parts(self) -> (usize, u64) {
+        (self.0.index(), self.0.version() as u64)
+    }
+}
+
+impl From<Id> for Index {
+    fn from(id: Id) -> Self {
+        Self(id)
+    }
+}
+
+impl From<Index> for Id {
+    fn from(index: Index) -> Self {
+        index.0
+    }
+}
+
+/// An arena built on top of OrderedVec, with the same API as the one from the generational-arena crate
+/// Replacing `use generational_arena::{Arena, Index};` with `use ordered_vec::arena::{Arena, Index};` should be enough to migrate
+pub struct Arena<T> {
+    /// The ordered vector that actually stores the elements
+    pub(crate) vec: OrderedVec<T>,
+}
+
+impl<T> Clone for Arena<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            vec: self.vec.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Arena<T>
+where
+    T: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Arena").field("vec", &self.vec).finish()
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self {
+            vec: OrderedVec::default(),
+        }
+    }
+}
+
+impl<T> From<OrderedVec<T>> for Arena<T> {
+    fn from(vec: OrderedVec<T>) -> Self {
+        Self { vec }
+    }
+}
+
+impl<T> From<Arena<T>> for OrderedVec<T> {
+    fn from(arena: Arena<T>) -> Self {
+        arena.vec
+    }
+}
+
+/// Actual code
+impl<T> Arena<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new arena that can store some elements without re-allocating
+    pub fn with_capacity(n: usize) -> Self {
+        let mut arena = Self::default();
+        arena.reserve(n);
+        arena
+    }
+    /// Reserve space for some more elements
+    pub fn reserve(&mut self, additional_capacity: usize) {
+        self.vec.slots.vec.reserve(additional_capacity);
+    }
+    /// Get the number of elements that we can store without re-allocating
+    pub fn capacity(&self) -> usize {
+        self.vec.slots.vec.capacity()
+    }
+    /// Insert an element. This never fails since the arena grows by itself
+    pub fn try_insert(&mut self, value: T) -> Result<Index, T> {
+        Ok(self.insert(value))
+    }
+    /// Insert an element
+    pub fn insert(&mut self, value: T) -> Index {
+        Index(self.vec.push_shove(value))
+    }
+    /// Insert an element that knows its own index
+    pub fn insert_with(&mut self, create: impl FnOnce(Index) -> T) -> Index {
+        let index = Index(self.vec.get_next_id());
+        self.vec.push_shove(create(index));
+        index
+    }
+    /// Remove an element
+    pub fn remove(&mut self, i: Index) -> Option<T> {
+        self.vec.remove(i.0)
+    }
+    /// Only keep the elements that return true
+    pub fn retain(&mut self, mut predicate: impl FnMut(Index, &mut T) -> bool) {
+        let removed = self
+            .vec
+            .iter_mut()
+            .filter_map(|(id, val)| (!predicate(Index(id), val)).then_some(id))
+            .collect::<Vec<_>>();
+        for id in removed {
+            self.vec.remove(id);
+        }
+    }
+    /// Check if an element is contained in the arena
+    pub fn contains(&self, i: Index) -> bool {
+        self.vec.get(i.0).is_some()
+    }
+    /// Get a reference to an element
+    pub fn get(&self, i: Index) -> Option<&T> {
+        self.vec.get(i.0)
+    }
+    /// Get a mutable reference to an element
+    pub fn get_mut(&mut self, i: Index) -> Option<&mut T> {
+        self.vec.get_mut(i.0)
+    }
+    /// Get mutable references to two different elements at the same time. This panics if both indices point to the same slot
+    pub fn get2_mut(&mut self, i1: Index, i2: Index) -> (Option<&mut T>, Option<&mut T>) {
+        assert_ne!(i1.0.index(), i2.0.index(), "Cannot get the same slot twice");
+        let first = self.vec.get_mut(i1.0).map(|x| x as *mut T);
+        let second = self.vec.get_mut(i2.0);
+        // SAFETY: the two slots are different, so the references can't alias
+        (first.map(|x| unsafe { &mut *x }), second)
+    }
+    /// Check the structural invariants of the arena, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()
+    }
+    /// Get the memory and fragmentation statistics of the arena
+    pub fn metrics(&self) -> Metrics {
+        self.vec.metrics()
+    }
+    /// Get the number of elements in the arena
+    pub fn len(&self) -> usize {
+        self.vec.count()
+    }
+    /// Check if the arena is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Remove all the elements
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+    /// Get an iterator over the elements, with their index
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &T)> {
+        self.vec.iter().map(|(id, val)| (Index(id), val))
+    }
+    /// Get a mutable iterator over the elements, with their index
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Index, &mut T)> {
+        self.vec.iter_mut().map(|(id, val)| (Index(id), val))
+    }
+    /// Remove all the elements, and return them with their index
+    pub fn drain(&mut self) -> impl Iterator<Item = (Index, T)> + '_ {
+        self.vec
+            .my_drain(|_, _| true)
+            .map(|(id, val)| (Index(id), val))
+    }
+}
+
+/// Traits
+impl<T> ops::Index<Index> for Arena<T> {
+    type Output = T;
+    fn index(&self, index: Index) -> &Self::Output {
+        self.get(index).expect("No element at index")
+    }
+}
+
+impl<T> ops::IndexMut<Index> for Arena<T> {
+    fn index_mut(&mut self, index: Index) -> &mut Self::Output {
+        self.get_mut(index).expect("No element at index")
+    }
+}
+
+impl<T> Extend<T> for Arena<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Arena<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut arena = Self::new();
+        arena.extend(iter);
+        arena
+    }
+}
@@ -0,0 +1,229 @@
+use std::io::{self, Read, Write};
+
+use crate::{
+    ordered_vec::OrderedVec,
+    patch::{Change, Patch},
+    slots::Slots,
+    utils::{Id, IdCodec},
+};
+
+/// The magic bytes at the start of every encoded ordered vector
+pub const BINARY_MAGIC: [u8; 4] = *b"OVEC";
+/// The version of the binary layout that we write. We can always read older versions
+pub const BINARY_VERSION: u16 = 1;
+/// The magic bytes at the start of every encoded patch
+pub const PATCH_MAGIC: [u8; 4] = *b"OVOP";
+
+/// Converts single elements to bytes and back, for the binary format of the ordered vector
+pub trait ElementCodec<T> {
+    /// Write a single element
+    fn encode(&self, elem: &T, output: &mut dyn Write) -> io::Result<()>;
+    /// Read a single element
+    fn decode(&self, input: &mut dyn Read) -> io::Result<T>;
+}
+
+/// An element codec that writes numbers as their little endian bytes
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LittleEndian;
+
+macro_rules! little_endian {
+    ($($t:ty),*) => {
+        $(
+            impl ElementCodec<$t> for LittleEndian {
+                fn encode(&self, elem: &$t, output: &mut dyn Write) -> io::Result<()> {
+                    output.write_all(&elem.to_le_bytes())
+                }
+                fn decode(&self, input: &mut dyn Read) -> io::Result<$t> {
+                    let mut bytes = [0; std::mem::size_of::<$t>()];
+                    input.read_exact(&mut bytes)?;
+                    Ok(<$t>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+little_endian!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+// Create an error for data that doesn't follow the binary layout
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Read a fixed amount of bytes
+fn read_array<const N: usize>(input: &mut dyn Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Binary format
+/// Everything is little endian, and the layout (version 1) is:
+/// - magic: the 4 bytes "OVEC"
+/// - format version: u16
+/// - codec: index bits (u8), tag bits (u8), tag (u32)
+/// - slot count: u64
+/// - occupancy bitmap: one bit per slot, slot i is bit i % 8 of byte i / 8, padded to a whole byte
+/// - versions: one u32 per slot, including the vacant ones
+/// - free list: its length (u64), then the index of each free slot (u64), in the order they will get re-used
+/// - values: the element codec output of each valid element, sorted by index
+impl<T> OrderedVec<T> {
+    /// Write the ordered vector using the stable binary format, including its holes and versions
+    pub fn encode(
+        &self,
+        output: &mut impl Write,
+        elements: &impl ElementCodec<T>,
+    ) -> io::Result<()> {
+        let codec = self.codec;
+        output.write_all(&BINARY_MAGIC)?;
+        output.write_all(&BINARY_VERSION.to_le_bytes())?;
+        output.write_all(&[codec.index_bits() as u8, codec.tag_bits() as u8])?;
+        output.write_all(&codec.tag().to_le_bytes())?;
+        let slots = &self.slots.vec;
+        output.write_all(&(slots.len() as u64).to_le_bytes())?;
+        // Occupancy bitmap
+        let mut bitmap = vec![0u8; slots.len().div_ceil(8)];
+        for (index, (val, _)) in slots.iter().enumerate() {
+            if val.is_some() {
+                bitmap[index / 8] |= 1 << (index % 8);
+            }
+        }
+        output.write_all(&bitmap)?;
+        for (_, version) in slots.iter() {
+            output.write_all(&version.to_le_bytes())?;
+        }
+        // The free list is stored in reverse, so the slot that gets re-used first comes first
+        let missing = &self.slots.missing;
+        output.write_all(&(missing.len() as u64).to_le_bytes())?;
+        for index in missing.iter().rev() {
+            output.write_all(&(*index as u64).to_le_bytes())?;
+        }
+        for val in self.slots.iter_elements() {
+            elements.encode(val, output)?;
+        }
+        Ok(())
+    }
+    /// Read an ordered vector that was written using encode(). The IDs of the elements stay the same
+    pub fn decode(input: &mut impl Read, elements: &impl ElementCodec<T>) -> io::Result<Self> {
+        if read_array::<4>(input)? != BINARY_MAGIC {
+            return Err(invalid("Not an encoded ordered vector"));
+        }
+        let version = u16::from_le_bytes(read_array(input)?);
+        if version == 0 || version > BINARY_VERSION {
+            return Err(invalid("Unsupported binary format version"));
+        }
+        let [index_bits, tag_bits] = read_array(input)?;
+        let tag = u32::from_le_bytes(read_array(input)?);
+        if index_bits == 0 || index_bits >= 64 || tag_bits > 32 || index_bits + tag_bits >= 64 {
+            return Err(invalid("Invalid ID layout"));
+        }
+        let codec = IdCodec::new(index_bits as u32)
+            .with_tag_bits(tag_bits as u32)
+            .with_tag(tag);
+        let len = u64::from_le_bytes(read_array(input)?) as usize;
+        // Don't trust the slot count for allocating, the data might be truncated
+        let mut bitmap = Vec::new();
+        input
+            .take(len.div_ceil(8) as u64)
+            .read_to_end(&mut bitmap)?;
+        if bitmap.len() != len.div_ceil(8) {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let mut versions = Vec::new();
+        for _ in 0..len {
+            versions.push(u32::from_le_bytes(read_array(input)?));
+        }
+        let missing_len = u64::from_le_bytes(read_array(input)?) as usize;
+        let mut missing = Vec::new();
+        for _ in 0..missing_len.min(len) {
+            missing.push(u64::from_le_bytes(read_array(input)?) as usize);
+        }
+        missing.reverse();
+        // Now we can read the elements themselves
+        let mut vec = Vec::new();
+        for (index, version) in versions.into_iter().enumerate() {
+            let occupied = bitmap[index / 8] & (1 << (index % 8)) != 0;
+            let val = if occupied {
+                Some(elements.decode(input)?)
+            } else {
+                None
+            };
+            vec.push((val, version));
+        }
+        // Make sure that the free list contains exactly the vacant slots
+        let mut free = vec.iter().map(|(val, _)| val.is_none()).collect::<Vec<_>>();
+        let valid = missing_len == missing.len()
+            && missing
+                .iter()
+                .all(|index| free.get_mut(*index).map(std::mem::take).unwrap_or_default())
+            && !free.contains(&true);
+        if !valid {
+            return Err(invalid("The free list does not match the occupancy bitmap"));
+        }
+        Ok(Self {
+            slots: Slots {
+                vec,
+                missing,
+                ..Slots::default()
+            },
+            codec,
+            graveyard: Vec::new(),
+            tags: Vec::new(),
+        })
+    }
+}
+
+/// Binary format of patches
+/// Everything is little endian, and the layout (version 1) is:
+/// - magic: the 4 bytes "OVOP"
+/// - format version: u16
+/// - change count: u64
+/// - changes: a kind byte (0 = insert, 1 = remove, 2 = update), the raw ID (u64), then the element codec output for inserts and updates
+impl<T> Patch<T> {
+    /// Write the changes of the patch using the stable binary format, so they can be stored or sent over the network and replayed later
+    pub fn encode(
+        &self,
+        output: &mut impl Write,
+        elements: &impl ElementCodec<T>,
+    ) -> io::Result<()> {
+        output.write_all(&PATCH_MAGIC)?;
+        output.write_all(&BINARY_VERSION.to_le_bytes())?;
+        output.write_all(&(self.changes.len() as u64).to_le_bytes())?;
+        for change in self.changes.iter() {
+            let (kind, id, elem) = match change {
+                Change::Insert(id, elem) => (0u8, id, Some(elem)),
+                Change::Remove(id) => (1u8, id, None),
+                Change::Update(id, elem) => (2u8, id, Some(elem)),
+            };
+            output.write_all(&[kind])?;
+            output.write_all(&id.to_u64().to_le_bytes())?;
+            if let Some(elem) = elem {
+                elements.encode(elem, output)?;
+            }
+        }
+        Ok(())
+    }
+    /// Read a patch that was written using encode()
+    pub fn decode(input: &mut impl Read, elements: &impl ElementCodec<T>) -> io::Result<Self> {
+        if read_array::<4>(input)? != PATCH_MAGIC {
+            return Err(invalid("Not an encoded patch"));
+        }
+        let version = u16::from_le_bytes(read_array(input)?);
+        if version == 0 || version > BINARY_VERSION {
+            return Err(invalid("Unsupported binary format version"));
+        }
+        let len = u64::from_le_bytes(read_array(input)?);
+        // Don't trust the change count for allocating, the data might be truncated
+        let mut changes = Vec::new();
+        for _ in 0..len {
+            let [kind] = read_array(input)?;
+            let id = Id::from(u64::from_le_bytes(read_array(input)?));
+            changes.push(match kind {
+                0 => Change::Insert(id, elements.decode(input)?),
+                1 => Change::Remove(id),
+                2 => Change::Update(id, elements.decode(input)?),
+                _ => return Err(invalid("Unknown change kind")),
+            });
+        }
+        Ok(Self { changes })
+    }
+}
@@ -0,0 +1,184 @@
+use std::{fmt::Debug, ops::Index};
+
+use crate::{invariant::InvariantError, metrics::Metrics, ordered_vec::OrderedVec, utils::Id};
+
+// Marks the end of the eviction list
+const NIL: usize = usize::MAX;
+
+/// Decides which element gets evicted when we push into a full BoundedOrderedVec
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum EvictionPolicy {
+    /// Evict the element that was added first
+    #[default]
+    OldestInsert,
+    /// Evict the element that was accessed the longest time ago. Calling get() or get_mut() counts as an access
+    LeastRecentlyUsed,
+}
+
+/// An ordered vector with a maximum number of elements, that evicts an element whenever we push past that maximum
+/// The elements are kept inside a doubly linked list (threaded through their slot indices) that is sorted by insertion or access, so evicting is O(1)
+/// With the LRU policy, this is basically an LRU cache with stable IDs
+pub struct BoundedOrderedVec<T> {
+    /// The elements
+    pub(crate) vec: OrderedVec<T>,
+    /// The previous and next slot of each slot inside the eviction list
+    pub(crate) links: Vec<(usize, usize)>,
+    /// The slot that will be evicted next
+    pub(crate) oldest: usize,
+    /// The slot that was added or accessed last
+    pub(crate) newest: usize,
+    /// The maximum number of elements
+    pub(crate) capacity: usize,
+    /// How we pick the element to evict
+    pub(crate) policy: EvictionPolicy,
+}
+
+impl<T: Debug> Debug for BoundedOrderedVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoundedOrderedVec")
+            .field("vec", &self.vec)
+            .field("capacity", &self.capacity)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+/// Eviction list magic
+impl<T> BoundedOrderedVec<T> {
+    // Remove a slot from the eviction list
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = self.links[index];
+        match prev {
+            NIL => self.oldest = next,
+            prev => self.links[prev].1 = next,
+        }
+        match next {
+            NIL => self.newest = prev,
+            next => self.links[next].0 = prev,
+        }
+    }
+    // Add a slot at the newest end of the eviction list
+    fn link_newest(&mut self, index: usize) {
+        if index >= self.links.len() {
+            self.links.resize(index + 1, (NIL, NIL));
+        }
+        self.links[index] = (self.newest, NIL);
+        match self.newest {
+            NIL => self.oldest = index,
+            newest => self.links[newest].1 = index,
+        }
+        self.newest = index;
+    }
+    // Move a slot to the newest end, if we care about accesses
+    fn touch(&mut self, index: usize) {
+        if self.policy == EvictionPolicy::LeastRecentlyUsed && self.newest != index {
+            self.unlink(index);
+            self.link_newest(index);
+        }
+    }
+}
+
+/// Actual code
+impl<T> BoundedOrderedVec<T> {
+    /// Create a new bounded ordered vector that can contain at most `capacity` elements
+    pub fn new(capacity: usize, policy: EvictionPolicy) -> Self {
+        assert!(capacity > 0, "The capacity must be at least 1");
+        Self {
+            vec: OrderedVec::default(),
+            links: Vec::new(),
+            oldest: NIL,
+            newest: NIL,
+            capacity,
+            policy,
+        }
+    }
+    /// Get the maximum number of elements
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Get the eviction policy
+    pub fn policy(&self) -> EvictionPolicy {
+        self.policy
+    }
+    /// Get the ID of the element that will be evicted next
+    pub fn next_evicted(&self) -> Option<Id> {
+        let index = self.oldest;
+        (index != NIL).then(|| self.vec.codec.encode(index, self.vec.slots.vec[index].1))
+    }
+    /// Add an element to the ordered vector. If we were full, this evicts an element first and returns it
+    pub fn push_shove(&mut self, elem: T) -> (Id, Option<(Id, T)>) {
+        let evicted = if self.vec.count() >= self.capacity {
+            let id = self.next_evicted().unwrap();
+            self.remove(id).map(|elem| (id, elem))
+        } else {
+            None
+        };
+        let id = self.vec.push_shove(elem);
+        self.link_newest(id.index());
+        (id, evicted)
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let elem = self.vec.remove(id)?;
+        self.unlink(id.index());
+        Some(elem)
+    }
+    /// Get a reference to an element, and mark it as used
+    pub fn get(&mut self, id: Id) -> Option<&T> {
+        self.vec.get(id)?;
+        self.touch(id.index());
+        self.vec.get(id)
+    }
+    /// Get a mutable reference to an element, and mark it as used
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        self.vec.get(id)?;
+        self.touch(id.index());
+        self.vec.get_mut(id)
+    }
+    /// Get a reference to an element without marking it as used
+    pub fn peek(&self, id: Id) -> Option<&T> {
+        self.vec.get(id)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.vec.metrics();
+        metrics.bytes_allocated += self.links.capacity() * std::mem::size_of::<(usize, usize)>();
+        metrics
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.vec.count()
+    }
+}
+
+/// Iter magic
+impl<T> BoundedOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element. This does not mark them as used
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.vec.iter()
+    }
+    /// Get an iterator over the IDs of the elements, from the next one that gets evicted to the newest one
+    pub fn iter_eviction_order(&self) -> impl Iterator<Item = Id> + '_ {
+        let mut index = self.oldest;
+        std::iter::from_fn(move || {
+            if index == NIL {
+                return None;
+            }
+            let id = self.vec.codec.encode(index, self.vec.slots.vec[index].1);
+            index = self.links[index].1;
+            Some(id)
+        })
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for BoundedOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.peek(index).unwrap()
+    }
+}
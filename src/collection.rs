@@ -0,0 +1,98 @@
+use crate::{
+    ordered_vec::OrderedVec, shareable_ordered_vec::ShareableOrderedVec,
+    unversioned_ordered_vec::UnversionnedOrderedVec, utils::Id,
+};
+
+/// The common operations of the ordered vectors, so we can write code that works with any of them
+/// The shareable ordered vector only implements this for its owner thread, since all the methods need to be called on the collection directly
+pub trait OrderedCollection {
+    /// The type of the elements
+    type Elem;
+    /// The key that gets returned when we add an element, and that we use to fetch it afterwards
+    type Key: Copy;
+    /// Add an element, and return its key
+    fn push(&mut self, elem: Self::Elem) -> Self::Key;
+    /// Get a reference to an element
+    fn get(&self, key: Self::Key) -> Option<&Self::Elem>;
+    /// Get a mutable reference to an element
+    fn get_mut(&mut self, key: Self::Key) -> Option<&mut Self::Elem>;
+    /// Remove an element
+    fn remove(&mut self, key: Self::Key) -> Option<Self::Elem>;
+    /// Get an iterator over the valid elements, with the key of each element
+    fn iter(&self) -> impl Iterator<Item = (Self::Key, &Self::Elem)>;
+    /// Get the number of valid elements
+    fn count(&self) -> usize;
+}
+
+impl<T> OrderedCollection for OrderedVec<T> {
+    type Elem = T;
+    type Key = Id;
+    fn push(&mut self, elem: T) -> Id {
+        self.push_shove(elem)
+    }
+    fn get(&self, key: Id) -> Option<&T> {
+        OrderedVec::get(self, key)
+    }
+    fn get_mut(&mut self, key: Id) -> Option<&mut T> {
+        OrderedVec::get_mut(self, key)
+    }
+    fn remove(&mut self, key: Id) -> Option<T> {
+        OrderedVec::remove(self, key)
+    }
+    fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        OrderedVec::iter(self)
+    }
+    fn count(&self) -> usize {
+        OrderedVec::count(self)
+    }
+}
+
+impl<T> OrderedCollection for UnversionnedOrderedVec<T> {
+    type Elem = T;
+    type Key = usize;
+    fn push(&mut self, elem: T) -> usize {
+        self.push_shove(elem)
+    }
+    fn get(&self, key: usize) -> Option<&T> {
+        UnversionnedOrderedVec::get(self, key)
+    }
+    fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        UnversionnedOrderedVec::get_mut(self, key)
+    }
+    fn remove(&mut self, key: usize) -> Option<T> {
+        UnversionnedOrderedVec::remove(self, key)
+    }
+    fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        UnversionnedOrderedVec::iter(self)
+    }
+    fn count(&self) -> usize {
+        UnversionnedOrderedVec::count(self)
+    }
+}
+
+impl<T> OrderedCollection for ShareableOrderedVec<T> {
+    type Elem = T;
+    type Key = Id;
+    fn push(&mut self, elem: T) -> Id {
+        // We own the collection, so nobody else can reserve this ID in the meantime
+        let id = self.get_next_id_increment();
+        // The ID was reserved by us, so it is always valid
+        self.insert(id, elem).unwrap();
+        id
+    }
+    fn get(&self, key: Id) -> Option<&T> {
+        ShareableOrderedVec::get(self, key)
+    }
+    fn get_mut(&mut self, key: Id) -> Option<&mut T> {
+        ShareableOrderedVec::get_mut(self, key)
+    }
+    fn remove(&mut self, key: Id) -> Option<T> {
+        ShareableOrderedVec::remove(self, key)
+    }
+    fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        ShareableOrderedVec::iter(self)
+    }
+    fn count(&self) -> usize {
+        ShareableOrderedVec::count(self)
+    }
+}
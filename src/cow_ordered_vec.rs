@@ -0,0 +1,280 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+    sync::Arc,
+};
+
+use crate::{
+    invariant::{check_len, InvariantError},
+    metrics::Metrics,
+    slots::{SlotStorage, SlotStorageMut, Slots},
+    utils::{Id, IdCodec, ReusePolicy},
+};
+
+/// The number of slots stored inside each chunk
+const CHUNK_SIZE: usize = 64;
+
+/// A single chunk of slots, shared between all the clones that didn't modify it yet
+type Chunk<T> = Arc<Vec<(Option<T>, u32)>>;
+
+/// The slot storage of the copy-on-write ordered vector, split into chunks that get copied on their first mutation
+pub(crate) struct Chunks<T> {
+    /// The chunks of slots, containing the elements and their versions
+    pub(crate) chunks: Arc<Vec<Chunk<T>>>,
+    /// The total number of slots
+    pub(crate) len: usize,
+}
+
+impl<T> Clone for Chunks<T> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for Chunks<T> {
+    fn default() -> Self {
+        Self {
+            chunks: Arc::new(Vec::new()),
+            len: 0,
+        }
+    }
+}
+
+impl<T> SlotStorage<T, u32> for Chunks<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn slot(&self, index: usize) -> Option<&(Option<T>, u32)> {
+        self.chunks.get(index / CHUNK_SIZE)?.get(index % CHUNK_SIZE)
+    }
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a (Option<T>, u32)>
+    where
+        T: 'a,
+    {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+    fn bytes_allocated(&self) -> usize {
+        self.chunks.capacity() * std::mem::size_of::<Chunk<T>>()
+            + self
+                .chunks
+                .iter()
+                .map(|chunk| chunk.capacity() * std::mem::size_of::<(Option<T>, u32)>())
+                .sum::<usize>()
+    }
+}
+
+// Mutating a slot copies its chunk if it is still shared
+impl<T: Clone> SlotStorageMut<T, u32> for Chunks<T> {
+    fn slot_mut(&mut self, index: usize) -> Option<&mut (Option<T>, u32)> {
+        let chunk = Arc::make_mut(&mut self.chunks).get_mut(index / CHUNK_SIZE)?;
+        Arc::make_mut(chunk).get_mut(index % CHUNK_SIZE)
+    }
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut (Option<T>, u32)>
+    where
+        T: 'a,
+    {
+        Arc::make_mut(&mut self.chunks)
+            .iter_mut()
+            .flat_map(|chunk| Arc::make_mut(chunk).iter_mut())
+    }
+    fn push(&mut self, slot: (Option<T>, u32)) -> Result<(), (Option<T>, u32)> {
+        // Add the slot in a new chunk if the last one is full
+        let chunks = Arc::make_mut(&mut self.chunks);
+        if self.len.is_multiple_of(CHUNK_SIZE) {
+            chunks.push(Arc::new(Vec::with_capacity(CHUNK_SIZE)));
+        }
+        Arc::make_mut(chunks.last_mut().unwrap()).push(slot);
+        self.len += 1;
+        Ok(())
+    }
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// An ordered vector that can be cloned in O(1), because its clones share their slots until they get modified
+/// The slots are split into chunks, and the first mutation of a chunk makes a deep copy of that chunk only
+pub struct CowOrderedVec<T> {
+    /// The slots, with their free list. These only get copied on the first mutation after a clone, and even then the chunks stay shared
+    pub(crate) slots: Arc<Slots<T, u32, Chunks<T>>>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+}
+
+impl<T> Clone for CowOrderedVec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Debug for CowOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CowOrderedVec")
+            .field("vec", &self.slots.vec.iter().collect::<Vec<_>>())
+            .field("missing", &self.slots.missing)
+            .finish()
+    }
+}
+
+impl<T> Default for CowOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            slots: Arc::new(Slots::default()),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Chunk magic
+impl<T> CowOrderedVec<T> {
+    /// Get the number of chunks that are still shared with another clone
+    pub fn shared_chunks(&self) -> usize {
+        let shared =
+            Arc::strong_count(&self.slots) > 1 || Arc::strong_count(&self.slots.vec.chunks) > 1;
+        self.slots
+            .vec
+            .chunks
+            .iter()
+            .filter(|chunk| shared || Arc::strong_count(chunk) > 1)
+            .count()
+    }
+}
+
+/// Actual code
+impl<T> CowOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Create a new ordered vector that re-uses its free slots using a specific policy
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            slots: Arc::new(Slots::with_policy(policy)),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Add an element to the ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id
+    where
+        T: Clone,
+    {
+        let slots = Arc::make_mut(&mut self.slots);
+        let (index, version) = slots.push_shove(elem);
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        slots.vec.slot_mut(index).unwrap().1 = version;
+        self.codec.encode(index, version)
+    }
+    /// Get the ID of the next element that we will add
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T>
+    where
+        T: Clone,
+    {
+        // Don't copy anything if the element does not exist
+        self.get(id)?;
+        let (index, version) = self.codec.checked_decode(id)?;
+        Arc::make_mut(&mut self.slots).remove(index, version)
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
+    }
+    /// Get a mutable reference to an element in the ordered vector. This copies the chunk of the element if it is shared
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T>
+    where
+        T: Clone,
+    {
+        self.get(id)?;
+        let (index, version) = self.codec.checked_decode(id)?;
+        Arc::make_mut(&mut self.slots).get_mut(index, version)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        check_len(
+            "chunks",
+            self.slots.vec.len,
+            self.slots.vec.chunks.iter().map(|chunk| chunk.len()).sum(),
+        )?;
+        self.slots.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector. Chunks that are shared with other clones are counted as well
+    pub fn metrics(&self) -> Metrics {
+        self.slots.metrics()
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the number of invalid elements in the ordered vector
+    pub fn count_invalid(&self) -> usize {
+        self.slots.count_invalid()
+    }
+    /// Clear the whole ordered vector. This does not affect the clones
+    pub fn clear(&mut self) {
+        self.slots = Arc::new(Slots {
+            policy: self.slots.policy,
+            ..Slots::default()
+        });
+    }
+}
+
+/// Iter magic
+impl<T> CowOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element. This copies every shared chunk
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)>
+    where
+        T: Clone,
+    {
+        let codec = self.codec;
+        Arc::make_mut(&mut self.slots)
+            .iter_mut()
+            .map(move |(index, version, val)| (codec.encode(index, version), val))
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for CowOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T: Clone> IndexMut<Id> for CowOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
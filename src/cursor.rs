@@ -0,0 +1,61 @@
+use crate::{ordered_vec::OrderedVec, utils::Id};
+
+/// A cursor that walks over the valid elements of an OrderedVec, and that can modify the vector while doing so
+/// The cursor always points at a valid element, or past the end of the vector
+pub struct CursorMut<'a, T> {
+    /// The ordered vector that we are walking over
+    pub(crate) vec: &'a mut OrderedVec<T>,
+    /// The index of the current slot
+    pub(crate) index: usize,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // Create a cursor that points at the first valid element
+    pub(crate) fn new(vec: &'a mut OrderedVec<T>) -> Self {
+        let mut cursor = Self { vec, index: 0 };
+        cursor.skip_invalid();
+        cursor
+    }
+    // Move forward until we reach a valid element or the end
+    fn skip_invalid(&mut self) {
+        let slots = &self.vec.slots.vec;
+        while self.index < slots.len() && slots[self.index].0.is_none() {
+            self.index += 1;
+        }
+    }
+    /// Get the ID of the current element
+    pub fn current_id(&self) -> Option<Id> {
+        let (val, version) = self.vec.slots.vec.get(self.index)?;
+        val.as_ref()?;
+        Some(self.vec.codec.encode(self.index, *version))
+    }
+    /// Get the current element, with its ID
+    pub fn current(&mut self) -> Option<(Id, &mut T)> {
+        let id = self.current_id()?;
+        Some((id, self.vec.slots.vec[self.index].0.as_mut().unwrap()))
+    }
+    /// Move to the next valid element. This returns false if we reached the end
+    pub fn advance(&mut self) -> bool {
+        if self.index < self.vec.slots.vec.len() {
+            self.index += 1;
+            self.skip_invalid();
+        }
+        self.index < self.vec.slots.vec.len()
+    }
+    /// Remove the current element, and move to the next valid element
+    pub fn remove_current(&mut self) -> Option<(Id, T)> {
+        let id = self.current_id()?;
+        let elem = self.vec.slots.remove_index(self.index)?;
+        self.advance();
+        Some((id, elem))
+    }
+    /// Add a new element to the vector, without moving the cursor
+    /// The element will only be visited later if it ended up in a slot that comes after the cursor
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        self.vec.push_shove(elem)
+    }
+    /// Check if the cursor went past the last element
+    pub fn is_done(&self) -> bool {
+        self.index >= self.vec.slots.vec.len()
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::{check_len, InvariantError},
+    metrics::Metrics,
+    slots::Slots,
+    utils::{Id, IdCodec},
+};
+
+/// An ordered vector that keeps its values packed tightly, so iterating over them is as fast as iterating over a Vec
+/// The IDs point to slots that store the position of the value, and removing a value swaps the last value into its place
+pub struct DenseOrderedVec<T> {
+    /// The values, without any holes
+    pub(crate) values: Vec<T>,
+    /// For each value, the index of the slot that points to it
+    pub(crate) owners: Vec<usize>,
+    /// The slots containing the position of each value and their versions
+    pub(crate) slots: Slots<usize, u32>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+}
+
+impl<T> Clone for DenseOrderedVec<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            owners: self.owners.clone(),
+            slots: self.slots.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Debug for DenseOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DenseOrderedVec")
+            .field("values", &self.values)
+            .field("owners", &self.owners)
+            .field("missing", &self.slots.missing)
+            .finish()
+    }
+}
+
+impl<T> Default for DenseOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            owners: Vec::new(),
+            slots: Slots::default(),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<T> DenseOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new dense ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this dense ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Add an element to the dense ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let (index, version) = self.slots.push_shove(self.values.len());
+        self.values.push(elem);
+        self.owners.push(index);
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec[index].1 = version;
+        self.codec.encode(index, version)
+    }
+    /// Get the ID of the next element that we will add
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
+    }
+    /// Remove an element that is contained in the vec. The last element gets moved into its place
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let position = self.slots.remove(index, version)?;
+        let elem = self.values.swap_remove(position);
+        self.owners.swap_remove(position);
+        // Update the slot of the element that got moved
+        if let Some(&owner) = self.owners.get(position) {
+            self.slots.vec[owner].0 = Some(position);
+        }
+        Some(elem)
+    }
+    /// Get a reference to an element in the dense ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let position = *self.slots.get(index, version)?;
+        self.values.get(position)
+    }
+    /// Get a mutable reference to an element in the dense ordered vector
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let position = *self.slots.get(index, version)?;
+        self.values.get_mut(position)
+    }
+    /// Check the structural invariants of the dense ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()?;
+        check_len("values", self.slots.count(), self.values.len())?;
+        check_len("owners", self.values.len(), self.owners.len())?;
+        // Each slot must point to a value that is owned by that same slot
+        for (index, _, &position) in self.slots.iter() {
+            if self.owners.get(position) != Some(&index) {
+                return Err(InvariantError::BrokenBacklink { index });
+            }
+        }
+        Ok(())
+    }
+    /// Get the memory and fragmentation statistics of the dense ordered vector
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.slots.metrics();
+        metrics.bytes_allocated += self.values.capacity() * std::mem::size_of::<T>()
+            + self.owners.capacity() * std::mem::size_of::<usize>();
+        metrics
+    }
+    /// Get the number of valid elements in the dense ordered vector
+    pub fn count(&self) -> usize {
+        self.values.len()
+    }
+    /// Get the values, packed tightly. Their order changes whenever we remove an element
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+    /// Get the values mutably, packed tightly. Their order changes whenever we remove an element
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+    /// Clear the whole dense ordered vector
+    pub fn clear(&mut self) -> Vec<T> {
+        self.slots.clear();
+        self.owners.clear();
+        std::mem::take(&mut self.values)
+    }
+}
+
+/// Iter magic
+impl<T> DenseOrderedVec<T> {
+    // Get the ID of the value at a specific position
+    fn id_at(codec: IdCodec, slots: &Slots<usize, u32>, owner: usize) -> Id {
+        codec.encode(owner, slots.vec[owner].1)
+    }
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.owners
+            .iter()
+            .zip(self.values.iter())
+            .map(|(&owner, val)| (Self::id_at(self.codec, &self.slots, owner), val))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let codec = self.codec;
+        let slots = &self.slots;
+        self.owners
+            .iter()
+            .zip(self.values.iter_mut())
+            .map(move |(&owner, val)| (Self::id_at(codec, slots, owner), val))
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for DenseOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> IndexMut<Id> for DenseOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::Debug,
+    ops::{Index, IndexMut},
+    time::{Duration, Instant},
+};
+
+use crate::{invariant::InvariantError, metrics::Metrics, ordered_vec::OrderedVec, utils::Id};
+
+/// An ordered vector whose elements can expire after some time, like the entries of a cache or a session table
+/// The deadlines are kept inside a min-heap, so purging the expired elements only looks at the ones that actually expired
+pub struct ExpiringOrderedVec<T> {
+    /// The elements and their deadline
+    pub(crate) vec: OrderedVec<(T, Option<Instant>)>,
+    /// The deadlines, sorted so the earliest one comes first. Entries whose element got removed or got a new deadline are skipped when purging
+    pub(crate) deadlines: BinaryHeap<Reverse<(Instant, Id)>>,
+}
+
+impl<T: Debug> Debug for ExpiringOrderedVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExpiringOrderedVec")
+            .field("vec", &self.vec)
+            .finish()
+    }
+}
+
+impl<T> Default for ExpiringOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            vec: OrderedVec::default(),
+            deadlines: BinaryHeap::new(),
+        }
+    }
+}
+
+/// Actual code
+impl<T> ExpiringOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add an element that never expires
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        self.vec.push_shove((elem, None))
+    }
+    /// Add an element that expires at a specific instant
+    pub fn insert_expiring_at(&mut self, elem: T, deadline: Instant) -> Id {
+        let id = self.vec.push_shove((elem, Some(deadline)));
+        self.deadlines.push(Reverse((deadline, id)));
+        id
+    }
+    /// Add an element that expires after some time
+    pub fn insert_with_ttl(&mut self, elem: T, ttl: Duration) -> Id {
+        self.insert_expiring_at(elem, Instant::now() + ttl)
+    }
+    /// Change the deadline of an element. None makes it live forever
+    pub fn set_expiry(&mut self, id: Id, deadline: Option<Instant>) -> bool {
+        let Some((_, old)) = self.vec.get_mut(id) else {
+            return false;
+        };
+        *old = deadline;
+        if let Some(deadline) = deadline {
+            self.deadlines.push(Reverse((deadline, id)));
+        }
+        true
+    }
+    /// Get the deadline of an element
+    pub fn expires_at(&self, id: Id) -> Option<Instant> {
+        self.vec.get(id)?.1
+    }
+    /// Remove all the elements that expired at `now`, and return them with their ID, from the earliest deadline to the latest
+    pub fn purge_expired(&mut self, now: Instant) -> Vec<(Id, T)> {
+        let mut expired = Vec::new();
+        while let Some(Reverse((deadline, id))) = self.deadlines.peek().copied() {
+            if deadline > now {
+                break;
+            }
+            self.deadlines.pop();
+            // Skip the entries that are out of date
+            if self.expires_at(id) == Some(deadline) {
+                let (elem, _) = self.vec.remove(id).unwrap();
+                expired.push((id, elem));
+            }
+        }
+        expired
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        self.vec.remove(id).map(|(elem, _)| elem)
+    }
+    /// Get a reference to an element. This still works for elements that expired but were not purged yet
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.vec.get(id).map(|(elem, _)| elem)
+    }
+    /// Get a mutable reference to an element. This still works for elements that expired but were not purged yet
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        self.vec.get_mut(id).map(|(elem, _)| elem)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.vec.metrics();
+        metrics.bytes_allocated +=
+            self.deadlines.capacity() * std::mem::size_of::<Reverse<(Instant, Id)>>();
+        metrics
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.vec.count()
+    }
+}
+
+/// Iter magic
+impl<T> ExpiringOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.vec.iter().map(|(id, (elem, _))| (id, elem))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        self.vec.iter_mut().map(|(id, (elem, _))| (id, elem))
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for ExpiringOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> IndexMut<Id> for ExpiringOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    alloc::{alloc, dealloc, handle_alloc_error, Layout},
+    ptr::{copy_nonoverlapping, null_mut},
+};
+
+use crate::{simple::OrderedVec, utils::Id};
+
+/// A single element allocated using the layout of its ordered vector
+struct Blob {
+    /// Pointer to the bytes of the element
+    ptr: *mut u8,
+    /// The layout that we used to allocate the element
+    layout: Layout,
+}
+
+impl Blob {
+    // Allocate a new blob and copy the bytes from the source into it
+    unsafe fn new(layout: Layout, src: *const u8) -> Self {
+        let ptr = if layout.size() == 0 {
+            std::ptr::without_provenance_mut(layout.align())
+        } else {
+            let ptr = alloc(layout);
+            if ptr.is_null() {
+                handle_alloc_error(layout)
+            }
+            copy_nonoverlapping(src, ptr, layout.size());
+            ptr
+        };
+        Self { ptr, layout }
+    }
+}
+
+impl Drop for Blob {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { dealloc(self.ptr, self.layout) }
+        }
+    }
+}
+
+/// An opaque ordered vector that stores elements of a fixed size and alignment, for use from C
+pub struct OvHandle {
+    /// The elements
+    vec: OrderedVec<Blob>,
+    /// The layout of each element
+    layout: Layout,
+}
+
+/// Create a new ordered vector whose elements have the given size and alignment. Returns null if the layout is invalid
+#[no_mangle]
+pub extern "C" fn ov_new(size: usize, align: usize) -> *mut OvHandle {
+    match Layout::from_size_align(size, align) {
+        Ok(layout) => Box::into_raw(Box::new(OvHandle {
+            vec: OrderedVec::new(),
+            layout,
+        })),
+        Err(_) => null_mut(),
+    }
+}
+
+/// Free an ordered vector and all of its elements
+/// # Safety
+/// The handle must come from [`ov_new`] and must not be used afterwards. Null handles are ignored
+#[no_mangle]
+pub unsafe extern "C" fn ov_free(handle: *mut OvHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Copy an element into the ordered vector, returning its ID
+/// # Safety
+/// The handle must be valid, and `src` must point to `size` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn ov_push(handle: *mut OvHandle, src: *const u8) -> u64 {
+    let handle = &mut *handle;
+    handle.vec.push_shove(Blob::new(handle.layout, src)).into()
+}
+
+/// Get a pointer to the bytes of an element, or null if the ID is not valid
+/// The pointer stays valid until the element gets removed or the ordered vector gets freed
+/// # Safety
+/// The handle must be valid
+#[no_mangle]
+pub unsafe extern "C" fn ov_get(handle: *const OvHandle, id: u64) -> *mut u8 {
+    let handle = &*handle;
+    handle
+        .vec
+        .get(Id::from(id))
+        .map_or(null_mut(), |blob| blob.ptr)
+}
+
+/// Remove an element, copying its bytes into `dst` if it is not null. Returns false if the ID was not valid
+/// # Safety
+/// The handle must be valid, and `dst` must either be null or point to `size` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn ov_remove(handle: *mut OvHandle, id: u64, dst: *mut u8) -> bool {
+    let handle = &mut *handle;
+    match handle.vec.remove(Id::from(id)) {
+        Some(blob) => {
+            if !dst.is_null() {
+                copy_nonoverlapping(blob.ptr, dst, blob.layout.size());
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Get the number of valid elements in the ordered vector
+/// # Safety
+/// The handle must be valid
+#[no_mangle]
+pub unsafe extern "C" fn ov_count(handle: *const OvHandle) -> usize {
+    (*handle).vec.count()
+}
@@ -0,0 +1,208 @@
+use std::{
+    cell::UnsafeCell,
+    fmt::Debug,
+    mem::MaybeUninit,
+    sync::atomic::{
+        AtomicU64, AtomicUsize,
+        Ordering::{Acquire, Relaxed, Release},
+    },
+};
+
+use crate::{
+    metrics::{largest_free_run, Metrics},
+    utils::Id,
+};
+
+// The states that a slot can be in. They are stored in the lower 32 bits of the slot's state
+const FRESH: u64 = 0;
+const VACANT: u64 = 1;
+const BUSY: u64 = 2;
+const OCCUPIED: u64 = 3;
+
+// Pack a version and a state into a single slot state
+fn pack(version: u32, state: u64) -> u64 {
+    ((version as u64) << 32) | state
+}
+
+// Unpack a slot state into its version and state
+fn unpack(packed: u64) -> (u32, u64) {
+    ((packed >> 32) as u32, packed & 0xFFFF_FFFF)
+}
+
+/// A single preallocated slot
+struct Slot<T> {
+    /// The version (upper 32 bits) and state (lower 32 bits) of this slot
+    state: AtomicU64,
+    /// The value, which is only initialized when the state is OCCUPIED
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A fixed capacity ordered vector that can be pushed to and removed from on any thread, without locking or allocating
+/// All the slots are allocated up front, and each slot gets claimed using a single compare and swap
+/// Pushing will visit every slot at most once, so it will return the element back if the vector is full instead of blocking
+pub struct FixedConcurrentOrderedVec<T> {
+    /// The preallocated slots
+    slots: Box<[Slot<T>]>,
+    /// The index at which we will start looking for a vacant slot
+    cursor: AtomicUsize,
+    /// The number of valid elements
+    count: AtomicUsize,
+    /// The number of times we re-used a vacant slot
+    reuses: AtomicU64,
+}
+
+// Elements can be moved in and out of the slots from any thread, so we only need T to be Send
+unsafe impl<T: Send> Sync for FixedConcurrentOrderedVec<T> {}
+
+impl<T> Debug for FixedConcurrentOrderedVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FixedConcurrentOrderedVec")
+            .field("capacity", &self.capacity())
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
+impl<T> Drop for FixedConcurrentOrderedVec<T> {
+    fn drop(&mut self) {
+        // Drop the elements that are still valid
+        for slot in self.slots.iter_mut() {
+            let (_, state) = unpack(*slot.state.get_mut());
+            if state == OCCUPIED {
+                unsafe { slot.value.get_mut().assume_init_drop() }
+            }
+        }
+    }
+}
+
+/// Actual code
+impl<T> FixedConcurrentOrderedVec<T> {
+    /// Create a new fixed concurrent ordered vector that can contain at most `capacity` elements
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity)
+                .map(|_| Slot {
+                    state: AtomicU64::new(pack(0, FRESH)),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+            cursor: AtomicUsize::new(0),
+            count: AtomicUsize::new(0),
+            reuses: AtomicU64::new(0),
+        }
+    }
+    /// Add an element to the ordered vector. This will give the element back if there are no vacant slots left
+    pub fn push_shove(&self, elem: T) -> Result<Id, T> {
+        let capacity = self.slots.len();
+        let start = self.cursor.load(Relaxed);
+        for offset in 0..capacity {
+            let index = (start + offset) % capacity;
+            let slot = &self.slots[index];
+            let current = slot.state.load(Relaxed);
+            // Fresh slots start at version 0, re-used slots get their version bumped
+            let version = match unpack(current) {
+                (_, FRESH) => 0,
+                (version, VACANT) => version.wrapping_add(1),
+                _ => continue,
+            };
+            // Try to claim the slot. If another thread beat us to it, just check the next one
+            if slot
+                .state
+                .compare_exchange(current, pack(version, BUSY), Acquire, Relaxed)
+                .is_ok()
+            {
+                unsafe { (*slot.value.get()).write(elem) };
+                slot.state.store(pack(version, OCCUPIED), Release);
+                self.cursor.store(index + 1, Relaxed);
+                self.count.fetch_add(1, Relaxed);
+                if unpack(current).1 == VACANT {
+                    self.reuses.fetch_add(1, Relaxed);
+                }
+                return Ok(Id::new(index, version));
+            }
+        }
+        // We are full
+        Err(elem)
+    }
+    /// Remove an element that is contained in the vec. This will fail if the element was already removed, or if the versions don't match up
+    pub fn remove(&self, id: Id) -> Option<T> {
+        if id.is_null() {
+            return None;
+        }
+        let slot = self.slots.get(id.index())?;
+        // Claim the slot, but only if it contains the element with the same version
+        slot.state
+            .compare_exchange(
+                pack(id.version(), OCCUPIED),
+                pack(id.version(), BUSY),
+                Acquire,
+                Relaxed,
+            )
+            .ok()?;
+        let elem = unsafe { (*slot.value.get()).assume_init_read() };
+        slot.state.store(pack(id.version(), VACANT), Release);
+        self.count.fetch_sub(1, Relaxed);
+        Some(elem)
+    }
+    /// Check if we contain a valid element with the given ID
+    pub fn contains(&self, id: Id) -> bool {
+        if id.is_null() {
+            return false;
+        }
+        self.slots
+            .get(id.index())
+            .map(|slot| slot.state.load(Acquire) == pack(id.version(), OCCUPIED))
+            .unwrap_or_default()
+    }
+    /// Get a mutable reference to an element in the ordered vector. We need exclusive access since other threads might remove the element otherwise
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        if id.is_null() {
+            return None;
+        }
+        let slot = self.slots.get_mut(id.index())?;
+        if *slot.state.get_mut() == pack(id.version(), OCCUPIED) {
+            Some(unsafe { slot.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.count.load(Relaxed)
+    }
+    /// Get the maximum number of elements that we can store
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector. This is only a rough picture if other threads are modifying it at the same time
+    pub fn metrics(&self) -> Metrics {
+        let occupied = self
+            .slots
+            .iter()
+            .map(|slot| matches!(unpack(slot.state.load(Relaxed)).1, BUSY | OCCUPIED));
+        Metrics {
+            bytes_allocated: std::mem::size_of_val::<[Slot<T>]>(&self.slots),
+            live: self.count(),
+            holes: self.capacity() - self.count(),
+            largest_free_run: largest_free_run(occupied),
+            reuses: self.reuses.load(Relaxed),
+        }
+    }
+}
+
+/// Iter magic
+impl<T> FixedConcurrentOrderedVec<T> {
+    /// Get a mutable iterator over the valid elements, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| match unpack(*slot.state.get_mut()) {
+                (version, OCCUPIED) => Some((Id::new(index, version), unsafe {
+                    slot.value.get_mut().assume_init_mut()
+                })),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::InvariantError,
+    metrics::{largest_free_run, Metrics},
+    slots::{FreeSlots, SlotStorage, SlotStorageMut, Slots},
+    utils::{Id, IdCodec, ReusePolicy},
+};
+
+/// The slot storage of the fixed ordered vector, stored inline. Only the first `len` slots were ever used
+pub(crate) struct InlineSlots<T, const N: usize> {
+    /// The slots containing the elements and their versions
+    pub(crate) slots: [(Option<T>, u32); N],
+    /// The number of slots that we have used at least once
+    pub(crate) len: usize,
+}
+
+impl<T: Clone, const N: usize> Clone for InlineSlots<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for InlineSlots<T, N> {
+    fn default() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| (None, 0)),
+            len: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> SlotStorage<T, u32> for InlineSlots<T, N> {
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn slot(&self, index: usize) -> Option<&(Option<T>, u32)> {
+        self.slots[..self.len].get(index)
+    }
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a (Option<T>, u32)>
+    where
+        T: 'a,
+    {
+        self.slots[..self.len].iter()
+    }
+    fn bytes_allocated(&self) -> usize {
+        std::mem::size_of_val(&self.slots)
+    }
+}
+
+impl<T, const N: usize> SlotStorageMut<T, u32> for InlineSlots<T, N> {
+    fn slot_mut(&mut self, index: usize) -> Option<&mut (Option<T>, u32)> {
+        self.slots[..self.len].get_mut(index)
+    }
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut (Option<T>, u32)>
+    where
+        T: 'a,
+    {
+        self.slots[..self.len].iter_mut()
+    }
+    fn push(&mut self, slot: (Option<T>, u32)) -> Result<(), (Option<T>, u32)> {
+        if self.len == N {
+            return Err(slot);
+        }
+        self.slots[self.len] = slot;
+        self.len += 1;
+        Ok(())
+    }
+    fn clear(&mut self) {
+        // Drop the elements in place, so nothing gets allocated
+        for slot in self.slots[..self.len].iter_mut() {
+            *slot = (None, 0);
+        }
+        self.len = 0;
+    }
+}
+
+/// The free list of the fixed ordered vector, stored inline. Only the first `len` entries are valid
+#[derive(Clone, Copy)]
+pub(crate) struct InlineFree<const N: usize> {
+    /// The indices that contain a null element
+    pub(crate) indices: [usize; N],
+    /// The number of indices in the free list
+    pub(crate) len: usize,
+}
+
+impl<const N: usize> Default for InlineFree<N> {
+    fn default() -> Self {
+        Self {
+            indices: [0; N],
+            len: 0,
+        }
+    }
+}
+
+impl<const N: usize> Debug for InlineFree<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<const N: usize> FreeSlots for InlineFree<N> {
+    fn as_slice(&self) -> &[usize] {
+        &self.indices[..self.len]
+    }
+    fn push(&mut self, index: usize) {
+        // There are never more free slots than slots
+        self.indices[self.len] = index;
+        self.len += 1;
+    }
+    fn remove(&mut self, position: usize) -> usize {
+        let index = self.indices[position];
+        self.indices.copy_within(position + 1..self.len, position);
+        self.len -= 1;
+        index
+    }
+    fn swap_remove(&mut self, position: usize) -> usize {
+        let index = self.indices[position];
+        self.len -= 1;
+        self.indices[position] = self.indices[self.len];
+        index
+    }
+    fn retain(&mut self, mut keep: impl FnMut(&usize) -> bool) {
+        let mut len = 0;
+        for position in 0..self.len {
+            if keep(&self.indices[position]) {
+                self.indices[len] = self.indices[position];
+                len += 1;
+            }
+        }
+        self.len = len;
+    }
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+    fn bytes_allocated(&self) -> usize {
+        std::mem::size_of_val(&self.indices)
+    }
+}
+
+/// An ordered vector with a fixed capacity, that stores its slots inline and never allocates
+/// This is useful on realtime threads where we can't allocate, since pushing an element into a full vector just gives it back
+pub struct FixedOrderedVec<T, const N: usize> {
+    /// The slots containing the elements and their versions, with their free list
+    pub(crate) slots: Slots<T, u32, InlineSlots<T, N>, InlineFree<N>>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+}
+
+impl<T, const N: usize> Clone for FixedOrderedVec<T, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T, const N: usize> Debug for FixedOrderedVec<T, N>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FixedOrderedVec")
+            .field("vec", &&self.slots.vec.slots[..self.slots.vec.len])
+            .field("missing", &self.slots.missing)
+            .finish()
+    }
+}
+
+impl<T, const N: usize> Default for FixedOrderedVec<T, N> {
+    fn default() -> Self {
+        Self {
+            slots: Slots::default(),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<T, const N: usize> FixedOrderedVec<T, N> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Create a new ordered vector that re-uses its free slots using a specific policy
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            slots: Slots::with_policy(policy),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Add an element to the ordered vector. If the vector is full, the element gets returned back
+    pub fn push_shove(&mut self, elem: T) -> Result<Id, T> {
+        let (index, version) = self.slots.try_push_shove(elem)?;
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec.slots[index].1 = version;
+        Ok(self.codec.encode(index, version))
+    }
+    /// Get the ID of the next element that we will add, if we have space for it
+    pub fn get_next_id(&self) -> Option<Id> {
+        if self.slots.missing.len == 0 && self.slots.vec.len == N {
+            return None;
+        }
+        let (index, version) = self.slots.next_slot();
+        Some(
+            self.codec
+                .encode(index, version & self.codec.version_mask()),
+        )
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.remove(index, version)
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
+    }
+    /// Get a mutable reference to an element in the ordered vector
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get_mut(index, version)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector. Nothing is allocated on the heap, so this reports the inline storage instead
+    /// The slots that were never used count as holes too
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            holes: N - self.count(),
+            largest_free_run: largest_free_run(
+                self.slots.vec.slots.iter().map(|(val, _)| val.is_some()),
+            ),
+            ..self.slots.metrics()
+        }
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the maximum number of elements that we can store
+    pub fn capacity(&self) -> usize {
+        N
+    }
+    /// Check if we can't add any more elements
+    pub fn is_full(&self) -> bool {
+        self.count() == N
+    }
+    /// Clear the whole ordered vector, dropping its elements
+    pub fn clear(&mut self) {
+        self.slots.reset();
+    }
+}
+
+/// Iter magic
+impl<T, const N: usize> FixedOrderedVec<T, N> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let codec = self.codec;
+        self.slots
+            .iter_mut()
+            .map(move |(index, version, val)| (codec.encode(index, version), val))
+    }
+}
+
+/// Traits
+impl<T, const N: usize> Index<Id> for FixedOrderedVec<T, N> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T, const N: usize> IndexMut<Id> for FixedOrderedVec<T, N> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
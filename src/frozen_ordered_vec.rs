@@ -0,0 +1,85 @@
+use std::{fmt::Debug, ops::Index, sync::Arc};
+
+use crate::{invariant::InvariantError, metrics::Metrics, ordered_vec::OrderedVec, utils::Id};
+
+/// A read-only ordered vector that can be cloned cheaply and shared between threads
+/// Reading from it does not need any locks, and once every other clone is dropped it can be thawed back into a normal OrderedVec
+pub struct FrozenOrderedVec<T> {
+    /// The frozen ordered vector
+    pub(crate) inner: Arc<OrderedVec<T>>,
+}
+
+impl<T> Clone for FrozenOrderedVec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Debug for FrozenOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrozenOrderedVec")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<T> From<OrderedVec<T>> for FrozenOrderedVec<T> {
+    fn from(vec: OrderedVec<T>) -> Self {
+        Self {
+            inner: Arc::new(vec),
+        }
+    }
+}
+
+/// Actual code
+impl<T> FrozenOrderedVec<T> {
+    /// Get a reference to an element in the frozen ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.inner.get(id)
+    }
+    /// Check if an element is contained in the frozen ordered vector
+    pub fn contains(&self, id: Id) -> bool {
+        self.inner.get(id).is_some()
+    }
+    /// Check the structural invariants of the frozen ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.inner.validate()
+    }
+    /// Get the memory and fragmentation statistics of the frozen ordered vector
+    pub fn metrics(&self) -> Metrics {
+        self.inner.metrics()
+    }
+    /// Get the number of valid elements in the frozen ordered vector
+    pub fn count(&self) -> usize {
+        self.inner.count()
+    }
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.inner.iter()
+    }
+    /// Get an iterator over the valid elements
+    pub fn iter_elements(&self) -> impl Iterator<Item = &T> {
+        self.inner.iter_elements()
+    }
+    /// Get the number of clones that share this frozen ordered vector, including this one
+    pub fn share_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+    /// Turn this back into a normal ordered vector. This only works if this is the last clone, otherwise we give it back
+    pub fn thaw(self) -> Result<OrderedVec<T>, Self> {
+        Arc::try_unwrap(self.inner).map_err(|inner| Self { inner })
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for FrozenOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
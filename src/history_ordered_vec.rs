@@ -0,0 +1,201 @@
+use std::{fmt::Debug, ops::Index};
+
+use crate::{invariant::InvariantError, metrics::Metrics, ordered_vec::OrderedVec, utils::Id};
+
+// An edit that reverts another edit
+enum Revert<T> {
+    // Remove an element that got inserted
+    Remove(Id),
+    // Put back an element that got removed, using its old ID
+    Restore(Id, T),
+    // Put back the old value of an element that got overwritten
+    Overwrite(Id, T),
+}
+
+/// An ordered vector that records each insert, remove and overwrite, so they can be undone and redone later
+/// Undoing a remove puts the element back with its old ID, so the IDs stored elsewhere stay valid
+/// If its slot got re-used in the meantime, the element gets a newer version instead, since rolling the version back would make the IDs of the other elements of that slot valid again
+pub struct HistoryOrderedVec<T> {
+    /// The elements
+    pub(crate) vec: OrderedVec<T>,
+    /// The edits that revert what we did, the last one comes last
+    undo: Vec<Revert<T>>,
+    /// The edits that revert what we undid, the last one comes last
+    redo: Vec<Revert<T>>,
+}
+
+impl<T: Debug> Debug for HistoryOrderedVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HistoryOrderedVec")
+            .field("vec", &self.vec)
+            .field("undo", &self.undo.len())
+            .field("redo", &self.redo.len())
+            .finish()
+    }
+}
+
+impl<T> Default for HistoryOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            vec: OrderedVec::default(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+}
+
+impl<T> From<OrderedVec<T>> for HistoryOrderedVec<T> {
+    fn from(vec: OrderedVec<T>) -> Self {
+        Self {
+            vec,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+}
+
+/// Actual code
+impl<T> HistoryOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // Record a new edit. This forgets everything that was undone
+    fn record(&mut self, revert: Revert<T>) {
+        self.undo.push(revert);
+        self.redo.clear();
+    }
+    // Make the recorded edits of an element use its new ID
+    fn rename(&mut self, old: Id, new: Id) {
+        for revert in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            let (Revert::Remove(id) | Revert::Restore(id, _) | Revert::Overwrite(id, _)) = revert;
+            if *id == old {
+                *id = new;
+            }
+        }
+    }
+    // Apply a revert edit, and return the edit that reverts it in turn
+    fn apply(&mut self, revert: Revert<T>) -> Revert<T> {
+        match revert {
+            Revert::Remove(id) => Revert::Restore(id, self.vec.remove(id).unwrap()),
+            Revert::Restore(id, elem) => {
+                let codec = self.vec.codec;
+                let (index, version) = codec.decode(id);
+                // Versions must never go backwards, so a slot that got re-used gives us a version above the one it has now
+                let current = self.vec.slots.vec[index].1;
+                if current == version {
+                    self.vec.slots.insert_at(index, version, elem);
+                    Revert::Remove(id)
+                } else {
+                    let version = current.wrapping_add(1) & codec.version_mask();
+                    self.vec.slots.insert_at(index, version, elem);
+                    let new = codec.encode(index, version);
+                    self.rename(id, new);
+                    Revert::Remove(new)
+                }
+            }
+            Revert::Overwrite(id, elem) => {
+                let old = std::mem::replace(self.vec.get_mut(id).unwrap(), elem);
+                Revert::Overwrite(id, old)
+            }
+        }
+    }
+    /// Add an element to the ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let id = self.vec.push_shove(elem);
+        self.record(Revert::Remove(id));
+        id
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T>
+    where
+        T: Clone,
+    {
+        let elem = self.vec.remove(id)?;
+        self.record(Revert::Restore(id, elem.clone()));
+        Some(elem)
+    }
+    /// Replace an element with a new value, and return the old value
+    pub fn overwrite(&mut self, id: Id, elem: T) -> Option<T>
+    where
+        T: Clone,
+    {
+        let old = std::mem::replace(self.vec.get_mut(id)?, elem);
+        self.record(Revert::Overwrite(id, old.clone()));
+        Some(old)
+    }
+    /// Revert the last edit. This returns false if there was nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(revert) => {
+                let revert = self.apply(revert);
+                self.redo.push(revert);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Apply the last edit that got undone again. This returns false if there was nothing to redo
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(revert) => {
+                let revert = self.apply(revert);
+                self.undo.push(revert);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Get the number of edits that can be undone
+    pub fn undo_count(&self) -> usize {
+        self.undo.len()
+    }
+    /// Get the number of edits that can be redone
+    pub fn redo_count(&self) -> usize {
+        self.redo.len()
+    }
+    /// Forget all the recorded edits, without changing the elements
+    pub fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.vec.get(id)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.vec.metrics();
+        metrics.bytes_allocated +=
+            (self.undo.capacity() + self.redo.capacity()) * std::mem::size_of::<Revert<T>>();
+        metrics
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.vec.count()
+    }
+    /// Stop recording the edits, and get back the ordered vector
+    pub fn into_inner(self) -> OrderedVec<T> {
+        self.vec
+    }
+}
+
+/// Iter magic
+impl<T> HistoryOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.vec.iter()
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for HistoryOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
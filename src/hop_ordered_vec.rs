@@ -0,0 +1,240 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::{check_len, InvariantError},
+    metrics::Metrics,
+    slots::Slots,
+    utils::{Id, IdCodec},
+};
+
+/// An ordered vector that never moves its elements, but that can still iterate quickly when it contains a lot of holes
+/// It keeps a jump-counting skipfield alongside the slots, so iterating over a run of vacant slots is a single hop
+/// For a run of vacant slots that starts at `s` and ends at `e`, the skipfield contains the length of the run at both `s` and `e`,
+/// and the distance from `s` (plus one) for the slots in between. Occupied slots contain 0
+pub struct HopOrderedVec<T> {
+    /// The slots containing the elements and their versions
+    pub(crate) slots: Slots<T, u32>,
+    /// The skipfield, with one entry per slot
+    pub(crate) skip: Vec<usize>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+}
+
+impl<T> Clone for HopOrderedVec<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            skip: self.skip.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Debug for HopOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HopOrderedVec")
+            .field("vec", &self.slots.vec)
+            .field("missing", &self.slots.missing)
+            .field("skip", &self.skip)
+            .finish()
+    }
+}
+
+impl<T> Default for HopOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            slots: Slots::default(),
+            skip: Vec::new(),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Skipfield magic
+impl<T> HopOrderedVec<T> {
+    // Mark an occupied slot as vacant, merging it with the neighbouring runs
+    fn skip_erase(&mut self, index: usize) {
+        let skip = &mut self.skip;
+        let left = if index > 0 { skip[index - 1] } else { 0 };
+        let right = skip.get(index + 1).copied().unwrap_or_default();
+        let start = index - left;
+        let end = index + right;
+        let len = end - start + 1;
+        skip[start] = len;
+        skip[end] = len;
+        // Renumber everything between the start and the end. The left run was already numbered properly
+        let first = index.max(start + 1);
+        if first < end {
+            for (offset, value) in skip[first..end].iter_mut().enumerate() {
+                *value = first - start + offset + 1;
+            }
+        }
+    }
+    // Mark a vacant slot as occupied, splitting the run that contained it
+    fn skip_fill(&mut self, index: usize) {
+        let skip = &mut self.skip;
+        // Find the start of the run that contains this slot
+        let start = if index == 0 || skip[index - 1] == 0 {
+            index
+        } else {
+            index + 1 - skip[index]
+        };
+        let end = start + skip[start] - 1;
+        skip[index] = 0;
+        // The left part keeps its numbering, we just need to fix its ends
+        if index > start {
+            let len = index - start;
+            skip[start] = len;
+            skip[index - 1] = len;
+        }
+        // The right part starts over
+        if index < end {
+            let len = end - index;
+            for (offset, value) in skip[(index + 1)..end].iter_mut().enumerate() {
+                *value = offset + 1;
+            }
+            skip[index + 1] = len;
+            skip[end] = len;
+        }
+    }
+}
+
+/// Actual code
+impl<T> HopOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Add an element to the ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let (index, version) = self.slots.push_shove(elem);
+        if index == self.skip.len() {
+            self.skip.push(0);
+        } else {
+            self.skip_fill(index);
+        }
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec[index].1 = version;
+        self.codec.encode(index, version)
+    }
+    /// Get the ID of the next element that we will add
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let elem = self.slots.remove(index, version)?;
+        self.skip_erase(index);
+        Some(elem)
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
+    }
+    /// Get a mutable reference to an element in the ordered vector
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get_mut(index, version)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()?;
+        check_len("skipfield", self.slots.vec.len(), self.skip.len())
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.slots.metrics();
+        metrics.bytes_allocated += self.skip.capacity() * std::mem::size_of::<usize>();
+        metrics
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the number of invalid elements in the ordered vector
+    pub fn count_invalid(&self) -> usize {
+        self.slots.count_invalid()
+    }
+    /// Clear the whole ordered vector
+    pub fn clear(&mut self) -> Vec<Option<T>> {
+        self.skip.clear();
+        self.slots.clear()
+    }
+}
+
+/// Iter magic
+impl<T> HopOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element. This hops over runs of vacant slots
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        let codec = self.codec;
+        let skip = &self.skip;
+        let mut slots = self.slots.vec.iter().enumerate();
+        std::iter::from_fn(move || loop {
+            let (index, (val, version)) = slots.next()?;
+            // Hop over the whole run at once
+            if skip[index] > 0 {
+                if skip[index] > 1 {
+                    slots.nth(skip[index] - 2);
+                }
+                continue;
+            }
+            return Some((codec.encode(index, *version), val.as_ref().unwrap()));
+        })
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element. This hops over runs of vacant slots
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let codec = self.codec;
+        let skip = &self.skip;
+        let mut slots = self.slots.vec.iter_mut().enumerate();
+        std::iter::from_fn(move || loop {
+            let (index, (val, version)) = slots.next()?;
+            // Hop over the whole run at once
+            if skip[index] > 0 {
+                if skip[index] > 1 {
+                    slots.nth(skip[index] - 2);
+                }
+                continue;
+            }
+            return Some((codec.encode(index, *version), val.as_mut().unwrap()));
+        })
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for HopOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> IndexMut<Id> for HopOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
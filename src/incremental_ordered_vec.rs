@@ -0,0 +1,298 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::InvariantError,
+    metrics::Metrics,
+    slots::{SlotStorage, SlotStorageMut, Slots},
+    utils::{Id, IdCodec, ReusePolicy},
+};
+
+/// A single slot, containing an element and its version
+type Slot<T> = (Option<T>, u32);
+
+/// The slot storage of the incremental ordered vector, which might be in the middle of moving into a bigger buffer
+pub(crate) struct Buffers<T> {
+    /// The buffer that all the slots end up in. While migrating, this only contains the slots that were moved already
+    pub(crate) current: Vec<Slot<T>>,
+    /// The full buffer that we are migrating away from. Slots past the length of the current buffer were not moved yet
+    pub(crate) old: Vec<Slot<T>>,
+    /// The slots that were added while migrating, that come after the old slots
+    pub(crate) tail: Vec<Slot<T>>,
+}
+
+impl<T> Default for Buffers<T> {
+    fn default() -> Self {
+        Self {
+            current: Vec::new(),
+            old: Vec::new(),
+            tail: Vec::new(),
+        }
+    }
+}
+
+/// Migration magic
+impl<T> Buffers<T> {
+    // Check if we are still moving slots into the bigger buffer
+    fn is_migrating(&self) -> bool {
+        !self.old.is_empty()
+    }
+    // Move up to `step` slots into the bigger buffer
+    fn migrate(&mut self, step: usize) {
+        for _ in 0..step {
+            if !self.is_migrating() {
+                break;
+            }
+            let index = self.current.len();
+            let slot = if index < self.old.len() {
+                std::mem::take(&mut self.old[index])
+            } else {
+                std::mem::take(&mut self.tail[index - self.old.len()])
+            };
+            self.current.push(slot);
+            // Every slot got moved, so we can free the old buffers
+            if self.current.len() == self.old.len() + self.tail.len() {
+                self.old = Vec::new();
+                self.tail = Vec::new();
+            }
+        }
+    }
+}
+
+impl<T> SlotStorage<T, u32> for Buffers<T> {
+    fn len(&self) -> usize {
+        self.current.len().max(self.old.len() + self.tail.len())
+    }
+    fn slot(&self, index: usize) -> Option<&Slot<T>> {
+        if index < self.current.len() {
+            self.current.get(index)
+        } else if index < self.old.len() {
+            self.old.get(index)
+        } else {
+            self.tail.get(index - self.old.len())
+        }
+    }
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Slot<T>>
+    where
+        T: 'a,
+    {
+        let moved = self.current.len();
+        self.current
+            .iter()
+            .chain(self.old.iter().skip(moved))
+            .chain(self.tail.iter().skip(moved.saturating_sub(self.old.len())))
+    }
+    fn bytes_allocated(&self) -> usize {
+        (self.current.capacity() + self.old.capacity() + self.tail.capacity())
+            * std::mem::size_of::<Slot<T>>()
+    }
+}
+
+impl<T> SlotStorageMut<T, u32> for Buffers<T> {
+    fn slot_mut(&mut self, index: usize) -> Option<&mut Slot<T>> {
+        if index < self.current.len() {
+            self.current.get_mut(index)
+        } else if index < self.old.len() {
+            self.old.get_mut(index)
+        } else {
+            self.tail.get_mut(index - self.old.len())
+        }
+    }
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut Slot<T>>
+    where
+        T: 'a,
+    {
+        let moved = self.current.len();
+        let skip_tail = moved.saturating_sub(self.old.len());
+        self.current
+            .iter_mut()
+            .chain(self.old.iter_mut().skip(moved))
+            .chain(self.tail.iter_mut().skip(skip_tail))
+    }
+    fn push(&mut self, slot: Slot<T>) -> Result<(), Slot<T>> {
+        if self.is_migrating() {
+            // The tail was allocated up front, so this never reallocates
+            self.tail.push(slot);
+        } else if self.current.len() == self.current.capacity() && !self.current.is_empty() {
+            // Start migrating into a buffer that is twice as big, instead of copying everything right now
+            let len = self.current.len();
+            self.old = std::mem::replace(&mut self.current, Vec::with_capacity(len * 2));
+            self.tail = Vec::with_capacity(len);
+            self.tail.push(slot);
+        } else {
+            self.current.push(slot);
+        }
+        Ok(())
+    }
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// An ordered vector that grows incrementally, so it never copies all of its elements at once
+/// When it runs out of capacity, it allocates a buffer twice as big, and then moves a bounded number of slots into it on every mutation
+/// The slots that were added while moving go inside a tail buffer that is allocated up front, so nothing reallocates in the meantime
+pub struct IncrementalOrderedVec<T> {
+    /// The slots containing the elements and their versions, stored in the migrating buffers, with their free list
+    pub(crate) slots: Slots<T, u32, Buffers<T>>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+    /// The maximum number of slots that we move on each mutation
+    pub(crate) step: usize,
+}
+
+impl<T> Debug for IncrementalOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IncrementalOrderedVec")
+            .field("vec", &self.slots.vec.iter().collect::<Vec<_>>())
+            .field("missing", &self.slots.missing)
+            .field("migrating", &self.is_migrating())
+            .finish()
+    }
+}
+
+impl<T> Default for IncrementalOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            slots: Slots::default(),
+            codec: IdCodec::default(),
+            step: 64,
+        }
+    }
+}
+
+/// Migration magic
+impl<T> IncrementalOrderedVec<T> {
+    /// Create a new ordered vector that moves at most `step` slots on each mutation. This must be at least 2, so the migration finishes before the tail fills up
+    pub fn with_migration_step(step: usize) -> Self {
+        assert!(step >= 2, "The migration step must be at least 2");
+        Self {
+            step,
+            ..Default::default()
+        }
+    }
+    /// Check if we are still moving slots into the bigger buffer
+    pub fn is_migrating(&self) -> bool {
+        self.slots.vec.is_migrating()
+    }
+    /// Move up to `step` slots into the bigger buffer. This gets called automatically on each mutation, but can also be called explicitly during idle time
+    /// This returns true if we are still migrating afterwards
+    pub fn migrate_step(&mut self) -> bool {
+        self.slots.vec.migrate(self.step);
+        self.is_migrating()
+    }
+}
+
+/// Actual code
+impl<T> IncrementalOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Create a new ordered vector that re-uses its free slots using a specific policy
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            slots: Slots::with_policy(policy),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Add an element to the ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let (index, version) = self.slots.push_shove(elem);
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec.slot_mut(index).unwrap().1 = version;
+        self.migrate_step();
+        self.codec.encode(index, version)
+    }
+    /// Get the ID of the next element that we will add
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let elem = self.slots.remove(index, version)?;
+        self.migrate_step();
+        Some(elem)
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
+    }
+    /// Get a mutable reference to an element in the ordered vector
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get_mut(index, version)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector. While migrating, this includes both buffers
+    pub fn metrics(&self) -> Metrics {
+        self.slots.metrics()
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the number of invalid elements in the ordered vector
+    pub fn count_invalid(&self) -> usize {
+        self.slots.count_invalid()
+    }
+    /// Clear the whole ordered vector, freeing all of its buffers
+    pub fn clear(&mut self) -> Vec<Option<T>> {
+        self.slots.clear()
+    }
+}
+
+/// Iter magic
+impl<T> IncrementalOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let codec = self.codec;
+        self.slots
+            .iter_mut()
+            .map(move |(index, version, val)| (codec.encode(index, version), val))
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for IncrementalOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> IndexMut<Id> for IncrementalOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
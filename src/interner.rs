@@ -0,0 +1,135 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+    ops::Index,
+};
+
+use crate::{
+    invariant::{check_len, InvariantError},
+    metrics::Metrics,
+    ordered_vec::OrderedVec,
+    utils::Id,
+};
+
+/// A collection that deduplicates its values, so interning the same value twice gives back the same ID
+/// Each value has a reference count, and its slot only gets freed once it has been released as many times as it was interned
+pub struct Interner<T: Hash + Eq> {
+    /// The values and their reference count
+    pub(crate) values: OrderedVec<(T, usize)>,
+    /// The IDs of the values, grouped by the hash of the value
+    pub(crate) lookup: HashMap<u64, Vec<Id>>,
+    /// The hasher used to hash the values
+    pub(crate) hasher: RandomState,
+}
+
+impl<T: Hash + Eq + Debug> Debug for Interner<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Interner")
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl<T: Hash + Eq> Default for Interner<T> {
+    fn default() -> Self {
+        Self {
+            values: OrderedVec::default(),
+            lookup: HashMap::new(),
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+/// Actual code
+impl<T: Hash + Eq> Interner<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Find the ID of a value that was already interned
+    pub fn find(&self, value: &T) -> Option<Id> {
+        let hash = self.hasher.hash_one(value);
+        self.lookup
+            .get(&hash)?
+            .iter()
+            .copied()
+            .find(|id| self.values.get(*id).map(|(x, _)| x) == Some(value))
+    }
+    /// Intern a value. If an equal value was already interned, its reference count gets incremented and we return its ID
+    /// This returns the ID of the value and its new reference count
+    pub fn intern(&mut self, value: T) -> (Id, usize) {
+        if let Some(id) = self.find(&value) {
+            let (_, count) = self.values.get_mut(id).unwrap();
+            *count += 1;
+            return (id, *count);
+        }
+        let hash = self.hasher.hash_one(&value);
+        let id = self.values.push_shove((value, 1));
+        self.lookup.entry(hash).or_default().push(id);
+        (id, 1)
+    }
+    /// Release a value once. When its reference count reaches zero, the value gets removed
+    /// This returns the new reference count, or None if the ID is invalid
+    pub fn release(&mut self, id: Id) -> Option<usize> {
+        let (_, count) = self.values.get_mut(id)?;
+        *count -= 1;
+        let count = *count;
+        if count == 0 {
+            let (value, _) = self.values.remove(id).unwrap();
+            let hash = self.hasher.hash_one(&value);
+            let bucket = self.lookup.get_mut(&hash).unwrap();
+            bucket.retain(|x| *x != id);
+            if bucket.is_empty() {
+                self.lookup.remove(&hash);
+            }
+        }
+        Some(count)
+    }
+    /// Get a reference to an interned value
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.values.get(id).map(|(value, _)| value)
+    }
+    /// Get the reference count of an interned value
+    pub fn ref_count(&self, id: Id) -> Option<usize> {
+        self.values.get(id).map(|(_, count)| *count)
+    }
+    /// Check the structural invariants of the interner, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.values.validate()?;
+        check_len(
+            "lookup",
+            self.values.count(),
+            self.lookup.values().map(|ids| ids.len()).sum(),
+        )
+    }
+    /// Get the memory and fragmentation statistics of the interner
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.values.metrics();
+        metrics.bytes_allocated += self.lookup.capacity() * std::mem::size_of::<(u64, Vec<Id>)>()
+            + self
+                .lookup
+                .values()
+                .map(|ids| ids.capacity() * std::mem::size_of::<Id>())
+                .sum::<usize>();
+        metrics
+    }
+    /// Get the number of unique values
+    pub fn count(&self) -> usize {
+        self.values.count()
+    }
+    /// Get an iterator over the unique values, with their ID and reference count
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T, usize)> {
+        self.values
+            .iter()
+            .map(|(id, (value, count))| (id, value, *count))
+    }
+}
+
+/// Traits
+impl<T: Hash + Eq> Index<Id> for Interner<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
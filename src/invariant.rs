@@ -0,0 +1,126 @@
+use std::fmt::Display;
+
+/// A broken structural invariant, returned by the validate() method of each collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantError {
+    /// The free list contains an index that is past the end of the slots
+    FreeSlotOutOfBounds { index: usize, len: usize },
+    /// The free list contains an index whose slot still holds an element
+    FreeSlotOccupied { index: usize },
+    /// The free list contains the same index more than once
+    DuplicateFreeSlot { index: usize },
+    /// A slot is empty, but its index is missing from the free list, so it will never be re-used
+    UntrackedFreeSlot { index: usize },
+    /// Some bookkeeping of the collection does not have the length it should have
+    LengthMismatch {
+        what: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// A slot points to a packed value that does not point back to it
+    BrokenBacklink { index: usize },
+}
+
+impl Display for InvariantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvariantError::FreeSlotOutOfBounds { index, len } => write!(
+                f,
+                "Free slot {} is out of bounds, there are only {} slots",
+                index, len
+            ),
+            InvariantError::FreeSlotOccupied { index } => {
+                write!(f, "Free slot {} still contains an element", index)
+            }
+            InvariantError::DuplicateFreeSlot { index } => {
+                write!(f, "Free slot {} was freed more than once", index)
+            }
+            InvariantError::UntrackedFreeSlot { index } => {
+                write!(f, "Slot {} is empty but is not in the free list", index)
+            }
+            InvariantError::LengthMismatch {
+                what,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected the {} to have a length of {}, but it has a length of {}",
+                what, expected, found
+            ),
+            InvariantError::BrokenBacklink { index } => write!(
+                f,
+                "Slot {} points to a value that does not point back to it",
+                index
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+// Cross-check a free list against the occupancy of each slot
+// Slots that are None were never initialized, so they are allowed to be missing from the free list
+pub(crate) fn check_free_list(
+    occupied: impl Iterator<Item = Option<bool>>,
+    missing: impl IntoIterator<Item = usize>,
+) -> Result<(), InvariantError> {
+    let occupied = occupied.collect::<Vec<_>>();
+    let mut freed = vec![false; occupied.len()];
+    for index in missing {
+        match occupied.get(index) {
+            None => {
+                return Err(InvariantError::FreeSlotOutOfBounds {
+                    index,
+                    len: occupied.len(),
+                })
+            }
+            Some(Some(true)) => return Err(InvariantError::FreeSlotOccupied { index }),
+            _ if freed[index] => return Err(InvariantError::DuplicateFreeSlot { index }),
+            _ => freed[index] = true,
+        }
+    }
+    let untracked = occupied
+        .iter()
+        .zip(freed)
+        .position(|(occupied, freed)| *occupied == Some(false) && !freed);
+    match untracked {
+        Some(index) => Err(InvariantError::UntrackedFreeSlot { index }),
+        None => Ok(()),
+    }
+}
+
+// Check that some bookkeeping has the expected length
+pub(crate) fn check_len(
+    what: &'static str,
+    expected: usize,
+    found: usize,
+) -> Result<(), InvariantError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(InvariantError::LengthMismatch {
+            what,
+            expected,
+            found,
+        })
+    }
+}
+
+// Panic with a detailed report if a mutation broke an invariant
+// This only runs the checks when the debug-validate feature is enabled, so it costs nothing otherwise
+#[inline]
+pub(crate) fn debug_validate(
+    operation: &str,
+    missing: &[usize],
+    check: impl FnOnce() -> Result<(), InvariantError>,
+) {
+    #[cfg(feature = "debug-validate")]
+    if let Err(err) = check() {
+        panic!(
+            "{} broke an invariant: {}\nFree list: {:?}",
+            operation, err, missing
+        );
+    }
+    #[cfg(not(feature = "debug-validate"))]
+    let _ = (operation, missing, check);
+}
@@ -0,0 +1,114 @@
+use std::{cmp::Ordering, iter::Peekable};
+
+use crate::utils::{Id, IdCodec};
+
+/// An iterator that walks over two iterators sorted by index at the same time, and only returns the elements whose ID is in both
+/// The iterators of OrderedVec, SecondaryOrderedVec and HopOrderedVec are all sorted by index, so they can be zipped together
+/// If the same slot has different versions in the two iterators, the element gets skipped. SparseSecondaryOrderedVec is not sorted, so use its get() instead
+pub struct Zip<A: Iterator, B: Iterator> {
+    a: A,
+    b: Peekable<B>,
+    /// The codecs used to unpack the indices of the IDs returned by A and B
+    codecs: (IdCodec, IdCodec),
+}
+
+/// Zip two iterators that are sorted by index, whose IDs use the default layout
+pub fn zip<A, B, X, Y>(a: A, b: B) -> Zip<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator<Item = (Id, X)>,
+    B: IntoIterator<Item = (Id, Y)>,
+{
+    zip_with(a, IdCodec::default(), b, IdCodec::default())
+}
+
+/// Zip two iterators that are sorted by index, unpacking the IDs of each one with the codec of the collection it came from
+pub fn zip_with<A, B, X, Y>(
+    a: A,
+    codec_a: IdCodec,
+    b: B,
+    codec_b: IdCodec,
+) -> Zip<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator<Item = (Id, X)>,
+    B: IntoIterator<Item = (Id, Y)>,
+{
+    Zip {
+        a: a.into_iter(),
+        b: b.into_iter().peekable(),
+        codecs: (codec_a, codec_b),
+    }
+}
+
+impl<A, B, X, Y> Iterator for Zip<A, B>
+where
+    A: Iterator<Item = (Id, X)>,
+    B: Iterator<Item = (Id, Y)>,
+{
+    type Item = (Id, X, Y);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        'outer: loop {
+            let (id, x) = self.a.next()?;
+            let index = self.codecs.0.decode(id).0;
+            // Skip the elements of B that come before this one
+            loop {
+                let (other, _) = self.b.peek()?;
+                match self.codecs.1.decode(*other).0.cmp(&index) {
+                    Ordering::Less => {
+                        self.b.next();
+                    }
+                    Ordering::Equal => break,
+                    Ordering::Greater => continue 'outer,
+                }
+            }
+            let (other, y) = self.b.next().unwrap();
+            // Same slot, but it might be an old version
+            if other == id {
+                return Some((id, x, y));
+            }
+        }
+    }
+}
+
+/// Iterate over the elements that are valid in all of the given iterators at the same time
+/// Each iterator must return (Id, value) and must be sorted by index, like the iter() and iter_mut() of the ordered vectors
+/// join!(a.iter_mut(), b.iter()) returns (Id, &mut A, &B), join!(a, b, c) returns (Id, A, B, C), and so on up to four iterators
+/// If the primary uses a custom codec, pass it first like join!(codec; a, b), since its secondaries share the same codec
+#[macro_export]
+macro_rules! join {
+    ($codec:expr; $a:expr, $b:expr $(,)?) => {
+        $crate::simple::zip_with($a, $codec, $b, $codec)
+    };
+    ($codec:expr; $a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::simple::zip_with(
+            $crate::join!($codec; $a, $b).map(|(id, a, b)| (id, (a, b))),
+            $codec,
+            $c,
+            $codec,
+        )
+        .map(|(id, (a, b), c)| (id, a, b, c))
+    };
+    ($codec:expr; $a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::simple::zip_with(
+            $crate::join!($codec; $a, $b, $c).map(|(id, a, b, c)| (id, (a, b, c))),
+            $codec,
+            $d,
+            $codec,
+        )
+        .map(|(id, (a, b, c), d)| (id, a, b, c, d))
+    };
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::simple::zip($a, $b)
+    };
+    ($a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::simple::zip($crate::join!($a, $b).map(|(id, a, b)| (id, (a, b))), $c)
+            .map(|(id, (a, b), c)| (id, a, b, c))
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::simple::zip(
+            $crate::join!($a, $b, $c).map(|(id, a, b, c)| (id, (a, b, c))),
+            $d,
+        )
+        .map(|(id, (a, b, c), d)| (id, a, b, c, d))
+    };
+}
@@ -0,0 +1,161 @@
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::{check_len, InvariantError},
+    metrics::Metrics,
+    ordered_vec::OrderedVec,
+    utils::Id,
+};
+
+/// An ordered vector whose elements can also be looked up by an external key, like an asset path
+/// The key to ID map is kept in sync by the vector itself, and each element stores its key so we can go the other way around
+pub struct KeyedOrderedVec<K: Hash + Eq + Clone, T> {
+    /// The elements and their key
+    pub(crate) vec: OrderedVec<(K, T)>,
+    /// The ID of each key
+    pub(crate) ids: HashMap<K, Id>,
+}
+
+impl<K: Hash + Eq + Clone + Debug, T: Debug> Debug for KeyedOrderedVec<K, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedOrderedVec")
+            .field("vec", &self.vec)
+            .finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone, T> Default for KeyedOrderedVec<K, T> {
+    fn default() -> Self {
+        Self {
+            vec: OrderedVec::default(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+/// Actual code
+impl<K: Hash + Eq + Clone, T> KeyedOrderedVec<K, T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Insert an element with a key. If the key was already used, the element gets replaced in place, keeping its ID, and we return the old one
+    pub fn insert(&mut self, key: K, elem: T) -> (Id, Option<T>) {
+        if let Some(id) = self.ids.get(&key).copied() {
+            let (_, old) = self.vec.get_mut(id).unwrap();
+            return (id, Some(std::mem::replace(old, elem)));
+        }
+        let id = self.vec.push_shove((key.clone(), elem));
+        self.ids.insert(key, id);
+        (id, None)
+    }
+    /// Get the ID of a key
+    pub fn id_of<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<Id>
+    where
+        K: Borrow<Q>,
+    {
+        self.ids.get(key).copied()
+    }
+    /// Get the key of an element
+    pub fn key_of(&self, id: Id) -> Option<&K> {
+        self.vec.get(id).map(|(key, _)| key)
+    }
+    /// Check if a key is contained in the vec
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.ids.contains_key(key)
+    }
+    /// Get a reference to an element using its key
+    pub fn get_by_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&T>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_by_id(self.id_of(key)?)
+    }
+    /// Get a mutable reference to an element using its key
+    pub fn get_by_key_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut T>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_by_id_mut(self.id_of(key)?)
+    }
+    /// Get a reference to an element using its ID
+    pub fn get_by_id(&self, id: Id) -> Option<&T> {
+        self.vec.get(id).map(|(_, elem)| elem)
+    }
+    /// Get a mutable reference to an element using its ID
+    pub fn get_by_id_mut(&mut self, id: Id) -> Option<&mut T> {
+        self.vec.get_mut(id).map(|(_, elem)| elem)
+    }
+    /// Remove an element using its key, and return its ID and the element
+    pub fn remove_by_key<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<(Id, T)>
+    where
+        K: Borrow<Q>,
+    {
+        let id = self.ids.remove(key)?;
+        let (_, elem) = self.vec.remove(id).unwrap();
+        Some((id, elem))
+    }
+    /// Remove an element using its ID, and return its key and the element
+    pub fn remove_by_id(&mut self, id: Id) -> Option<(K, T)> {
+        let (key, elem) = self.vec.remove(id)?;
+        self.ids.remove(&key);
+        Some((key, elem))
+    }
+    /// Clear the whole vec, including the keys
+    pub fn clear(&mut self) {
+        self.vec.clear();
+        self.ids.clear();
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()?;
+        check_len("key map", self.vec.count(), self.ids.len())
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.vec.metrics();
+        metrics.bytes_allocated += self.ids.capacity() * std::mem::size_of::<(K, Id)>();
+        metrics
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.vec.count()
+    }
+}
+
+/// Iter magic
+impl<K: Hash + Eq + Clone, T> KeyedOrderedVec<K, T> {
+    /// Get an iterator over the valid elements, with the ID and the key of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &K, &T)> {
+        self.vec.iter().map(|(id, (key, elem))| (id, key, elem))
+    }
+    /// Get a mutable iterator over the valid elements, with the ID and the key of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &K, &mut T)> {
+        self.vec
+            .iter_mut()
+            .map(|(id, (key, elem))| (id, &*key, elem))
+    }
+}
+
+/// Traits
+impl<K: Hash + Eq + Clone, T> Index<Id> for KeyedOrderedVec<K, T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get_by_id(index).unwrap()
+    }
+}
+
+impl<K: Hash + Eq + Clone, T> IndexMut<Id> for KeyedOrderedVec<K, T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_by_id_mut(index).unwrap()
+    }
+}
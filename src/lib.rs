@@ -1,13 +1,79 @@
 // Export
+mod any_ordered_vec;
+pub mod arena;
+mod binary;
+mod bounded_ordered_vec;
+mod collection;
+mod cow_ordered_vec;
+mod cursor;
+mod dense_ordered_vec;
+mod expiring_ordered_vec;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fixed_concurrent_ordered_vec;
+mod fixed_ordered_vec;
+mod frozen_ordered_vec;
+mod history_ordered_vec;
+mod hop_ordered_vec;
+mod incremental_ordered_vec;
+mod interner;
+mod invariant;
+mod join;
+mod keyed_ordered_vec;
+mod metrics;
+mod mirror;
 mod ordered_vec;
+mod paged_ordered_vec;
+mod patch;
+mod pinned_ordered_vec;
+mod pool;
+mod registry;
+mod secondary_ordered_vec;
 mod shareable_ordered_vec;
+mod slots;
+mod small_ordered_vec;
+mod sparse_secondary_ordered_vec;
+mod stable_ordered_vec;
 mod test;
+mod tracked_ordered_vec;
+mod transaction;
 mod unversioned_ordered_vec;
 pub mod utils;
 pub mod simple {
+    pub use super::any_ordered_vec::*;
+    pub use super::binary::*;
+    pub use super::bounded_ordered_vec::*;
+    pub use super::collection::*;
+    pub use super::cow_ordered_vec::*;
+    pub use super::cursor::*;
+    pub use super::dense_ordered_vec::*;
+    pub use super::expiring_ordered_vec::*;
+    pub use super::fixed_ordered_vec::*;
+    pub use super::frozen_ordered_vec::*;
+    pub use super::history_ordered_vec::*;
+    pub use super::hop_ordered_vec::*;
+    pub use super::incremental_ordered_vec::*;
+    pub use super::interner::*;
+    pub use super::invariant::*;
+    pub use super::join::*;
+    pub use super::keyed_ordered_vec::*;
+    pub use super::metrics::*;
+    pub use super::mirror::*;
     pub use super::ordered_vec::*;
+    pub use super::paged_ordered_vec::*;
+    pub use super::patch::*;
+    pub use super::pinned_ordered_vec::*;
+    pub use super::pool::*;
+    pub use super::registry::*;
+    pub use super::secondary_ordered_vec::*;
+    pub use super::small_ordered_vec::*;
+    pub use super::sparse_secondary_ordered_vec::*;
+    pub use super::stable_ordered_vec::*;
+    pub use super::tracked_ordered_vec::*;
+    pub use super::transaction::*;
     pub use super::unversioned_ordered_vec::*;
 }
 pub mod shareable {
+    pub use super::fixed_concurrent_ordered_vec::*;
     pub use super::shareable_ordered_vec::*;
 }
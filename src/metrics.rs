@@ -0,0 +1,29 @@
+/// Memory and fragmentation statistics of a collection, returned by the metrics() method of each collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Metrics {
+    /// The number of bytes allocated for the slots and the bookkeeping of the collection. This does not include heap memory owned by the elements themselves
+    pub bytes_allocated: usize,
+    /// The number of valid elements
+    pub live: usize,
+    /// The number of slots that are free, but that are still allocated
+    pub holes: usize,
+    /// The length of the longest run of consecutive free slots
+    pub largest_free_run: usize,
+    /// The total number of times a free slot got re-used since the creation of the collection
+    pub reuses: u64,
+}
+
+// Find the length of the longest run of free slots, given the occupancy of each slot
+pub(crate) fn largest_free_run(occupied: impl Iterator<Item = bool>) -> usize {
+    let mut largest = 0;
+    let mut current = 0;
+    for occupied in occupied {
+        if occupied {
+            current = 0;
+        } else {
+            current += 1;
+            largest = largest.max(current);
+        }
+    }
+    largest
+}
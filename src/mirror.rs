@@ -0,0 +1,124 @@
+use std::{fmt::Debug, ops::Index};
+
+use crate::{
+    invariant::InvariantError,
+    ordered_vec::OrderedVec,
+    patch::{Change, Patch},
+    utils::Id,
+};
+
+/// An ordered vector that records every change made to it, so they can be replayed onto shadow copies with the exact same IDs
+/// The changes can be sent to another thread as a Patch, so a render thread can keep a read-only copy of the simulation data for example
+pub struct Mirror<T: Clone> {
+    /// The primary elements
+    pub(crate) vec: OrderedVec<T>,
+    /// The changes that were not synced yet, the last one comes last
+    log: Vec<Change<T>>,
+}
+
+impl<T: Clone + Debug> Debug for Mirror<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mirror")
+            .field("vec", &self.vec)
+            .field("pending", &self.log.len())
+            .finish()
+    }
+}
+
+impl<T: Clone> Default for Mirror<T> {
+    fn default() -> Self {
+        Self {
+            vec: OrderedVec::default(),
+            log: Vec::new(),
+        }
+    }
+}
+
+impl<T: Clone> From<OrderedVec<T>> for Mirror<T> {
+    fn from(vec: OrderedVec<T>) -> Self {
+        Self {
+            vec,
+            log: Vec::new(),
+        }
+    }
+}
+
+/// Actual code
+impl<T: Clone> Mirror<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add an element to the primary ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let id = self.vec.push_shove(elem.clone());
+        self.log.push(Change::Insert(id, elem));
+        id
+    }
+    /// Remove an element from the primary ordered vector
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let elem = self.vec.remove(id)?;
+        self.log.push(Change::Remove(id));
+        Some(elem)
+    }
+    /// Give an element a new value, and return the old value
+    pub fn update(&mut self, id: Id, elem: T) -> Option<T> {
+        let old = std::mem::replace(self.vec.get_mut(id)?, elem.clone());
+        self.log.push(Change::Update(id, elem));
+        Some(old)
+    }
+    /// Get a reference to an element in the primary ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.vec.get(id)
+    }
+    /// Get the primary ordered vector. It can only be modified through the mirror, otherwise the changes would not get recorded
+    pub fn vec(&self) -> &OrderedVec<T> {
+        &self.vec
+    }
+    /// Get the number of changes that were not synced yet
+    pub fn pending(&self) -> usize {
+        self.log.len()
+    }
+    /// Create a new shadow that starts out identical to the primary ordered vector
+    /// This forgets the changes that were not synced yet, so any other shadow must be synced before creating a new one
+    pub fn shadow(&mut self) -> OrderedVec<T> {
+        self.log.clear();
+        self.vec.clone()
+    }
+    /// Take the changes that were not synced yet, so they can be applied to a shadow on another thread using apply_patch()
+    /// The patch can also be encoded and replayed later onto a fresh ordered vector using apply_ops(), like for lockstep networking or reproducing crash reports
+    pub fn take_patch(&mut self) -> Patch<T> {
+        Patch {
+            changes: std::mem::take(&mut self.log),
+        }
+    }
+    /// Apply the changes that were not synced yet to a shadow, so it ends up with the same elements and the same IDs
+    /// This returns false if the shadow did not match up with the primary ordered vector before the sync
+    pub fn sync(&mut self, shadow: &mut OrderedVec<T>) -> bool {
+        shadow.apply_patch(self.take_patch())
+    }
+    /// Check the structural invariants of the primary ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()
+    }
+    /// Get the number of valid elements in the primary ordered vector
+    pub fn count(&self) -> usize {
+        self.vec.count()
+    }
+}
+
+/// Iter magic
+impl<T: Clone> Mirror<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.vec.iter()
+    }
+}
+
+/// Traits
+impl<T: Clone> Index<Id> for Mirror<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
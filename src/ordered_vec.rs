@@ -1,18 +1,35 @@
 use std::{
+    collections::HashMap,
     fmt::Debug,
     ops::{Index, IndexMut},
+    sync::Arc,
 };
 
-use crate::utils::{from_id, to_id, IndexPair};
+#[cfg(feature = "typed-handles")]
+use crate::utils::Handle;
+use crate::{
+    cursor::CursorMut,
+    frozen_ordered_vec::FrozenOrderedVec,
+    invariant::InvariantError,
+    metrics::Metrics,
+    patch::{Change, Patch, Snapshot, MAX_REPLAY_GAP},
+    slots::Slots,
+    transaction::Transaction,
+    utils::{GrowthPolicy, Id, IdCodec, ReusePolicy},
+};
 
 /// A collection that keeps the ordering of its elements, even when deleting an element
 /// This also supports versioning, so if we add two elements and they have the same physical index, they will not have the same ID
 /// https://www.david-colson.com/2020/02/09/making-a-simple-ecs.html
 pub struct OrderedVec<T> {
-    /// A list of the current elements in the list
-    pub(crate) vec: Vec<(Option<T>, u32)>,
-    /// A list of the indices that contain a null element, so whenever we add a new element, we will add it there
-    pub(crate) missing: Vec<usize>,
+    /// The slots containing the elements and their versions
+    pub(crate) slots: Slots<T, u32>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+    /// The elements that were marked as removed, but that were not reclaimed yet
+    pub(crate) graveyard: Vec<(Id, T)>,
+    /// The tag bitset of each slot. Slots past the end of this have no tags
+    pub(crate) tags: Vec<u64>,
 }
 
 impl<T> Clone for OrderedVec<T>
@@ -21,8 +38,10 @@ where
 {
     fn clone(&self) -> Self {
         Self {
-            vec: self.vec.clone(),
-            missing: self.missing.clone(),
+            slots: self.slots.clone(),
+            codec: self.codec,
+            graveyard: self.graveyard.clone(),
+            tags: self.tags.clone(),
         }
     }
 }
@@ -33,8 +52,8 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("OrderedVec")
-            .field("vec", &self.vec)
-            .field("missing", &self.missing)
+            .field("vec", &self.slots.vec)
+            .field("missing", &self.slots.missing)
             .finish()
     }
 }
@@ -42,8 +61,10 @@ where
 impl<T> Default for OrderedVec<T> {
     fn default() -> Self {
         Self {
-            vec: Vec::new(),
-            missing: Vec::new(),
+            slots: Slots::default(),
+            codec: IdCodec::default(),
+            graveyard: Vec::new(),
+            tags: Vec::new(),
         }
     }
 }
@@ -54,199 +75,461 @@ impl<T> OrderedVec<T> {
     pub fn new() -> Self {
         Self::default()
     }
+    /// Create a new ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            slots: Slots::default(),
+            codec: codec.salted(),
+            graveyard: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+    /// Create a new ordered vector that re-uses its free slots using a specific policy
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            slots: Slots::with_policy(policy),
+            codec: IdCodec::default(),
+            graveyard: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+    /// Create a new ordered vector that grows its slots using a specific growth policy
+    pub fn with_growth_policy(policy: impl GrowthPolicy + 'static) -> Self {
+        let mut vec = Self::default();
+        vec.slots.growth = Some(Arc::new(policy));
+        vec
+    }
     /// Create Self using already existing elements
     pub fn from_valids(vals: Vec<T>) -> Self {
         Self {
-            vec: vals
-                .into_iter()
-                .map(|x| (Some(x), 0))
-                .collect::<Vec<(Option<T>, u32)>>(),
-            missing: Vec::new(),
+            slots: Slots::from_valids(vals),
+            codec: IdCodec::default(),
+            graveyard: Vec::new(),
+            tags: Vec::new(),
         }
     }
+    /// Freeze this ordered vector, so it can be shared between threads cheaply
+    pub fn freeze(self) -> FrozenOrderedVec<T> {
+        FrozenOrderedVec::from(self)
+    }
+    /// Get the codec used to pack the IDs of this ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Get the policy used to re-use the free slots
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        self.slots.policy
+    }
     /// Add an element to the ordered vector
-    pub fn push_shove(&mut self, elem: T) -> u64 {
-        if self.missing.is_empty() {
-            // Add the element normally
-            self.vec.push((Some(elem), 0));
-            to_id(IndexPair::new(self.vec.len() - 1, 0))
-        } else {
-            // If we have some null elements, we can validate the given element there
-            let index = self.missing.pop().unwrap();
-            let (old_val, old_version) = self.vec.get_mut(index as usize).unwrap();
-            *old_val = Some(elem);
-            *old_version += 1;
-            // Create an ID from an index and old version
-            to_id(IndexPair::new(index, *old_version))
-        }
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let (index, version) = self.slots.push_shove(elem);
+        self.clear_tags(index);
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec[index].1 = version;
+        self.codec.encode(index, version)
     }
     /// Get the index of the next element that we will add
     pub fn get_next_index(&self) -> usize {
-        // Normal push
-        if self.missing.is_empty() {
-            return self.vec.len();
-        }
-        // Shove
-        *self.missing.last().unwrap()
+        self.slots.next_slot().0
     }
     /// Get the ID of the next element that we will add
-    pub fn get_next_id(&self) -> u64 {
-        // Normal push
-        if self.missing.is_empty() {
-            return to_id(IndexPair::new(self.vec.len(), 0));
-        }
-        // Shove
-        let index = *self.missing.last().unwrap();
-        let (_, version) = self.vec.get(index).unwrap();
-        to_id(IndexPair::new(index, *version + 1))
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
     }
     /// Remove an element that is contained in the vec
-    pub fn remove(&mut self, id: u64) -> Option<T> {
-        let pair = from_id(id);
-        self.missing.push(pair.index as usize);
-        let (elem, version) = self.vec.get_mut(pair.index as usize)?;
-        // Only remove if the version is the same as well
-        if pair.version != *version {
-            return None;
-        }
-        std::mem::take(elem)
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.remove(index, version)
     }
     /// Remove an element that is contained in the vec. This does not check if the element's version matches up with the ID!
     pub fn remove_index(&mut self, index: usize) -> Option<T> {
-        self.missing.push(index);
-        let (elem, _) = self.vec.get_mut(index as usize)?;
-        std::mem::take(elem)
+        self.slots.remove_index(index)
     }
     /// Get a reference to an element in the ordered vector
-    pub fn get(&self, id: u64) -> Option<&T> {
-        let pair = from_id(id);
-        // First of all check if we *might* contain the cell
-        return if (pair.index as usize) < self.vec.len() {
-            // We contain the cell, but it might be null
-            let (cell, version) = self.vec.get(pair.index as usize)?;
-            // Check if the versions are the same
-            if pair.version == *version {
-                cell.as_ref()
-            } else {
-                None
-            }
-        } else {
-            // We do not contain the cell at all
-            None
-        };
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
     }
     /// Get a mutable reference to an element in the ordered vector
-    pub fn get_mut(&mut self, id: u64) -> Option<&mut T> {
-        let pair = from_id(id);
-        // First of all check if we *might* contain the cell
-        return if (pair.index as usize) < self.vec.len() {
-            // We contain the cell, but it might be null
-            let (cell, version) = self.vec.get_mut(pair.index as usize)?;
-            // Check if the versions are the same
-            if pair.version == *version {
-                cell.as_mut()
-            } else {
-                None
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get_mut(index, version)
+    }
+    /// Get a mutable reference to an element, or insert one at the exact index and version of the ID if its slot is empty
+    /// This returns None if the slot is occupied by another element, with a different version
+    pub fn get_mut_or_insert_with<F>(&mut self, id: Id, f: F) -> Option<&mut T>
+    where
+        F: FnOnce() -> T,
+    {
+        let (index, version) = self.codec.checked_decode(id)?;
+        match self.slots.vec.get(index) {
+            Some((Some(_), cell_version)) if *cell_version != version => return None,
+            Some((Some(_), _)) => {}
+            _ => {
+                self.slots.insert_at(index, version, f());
+                self.clear_tags(index);
             }
-        } else {
-            // We do not contain the cell at all
-            None
-        };
+        }
+        self.slots.get_mut(index, version)
+    }
+    /// Overwrite an element, and return the old one. The version does not change, so the ID stays valid
+    pub fn replace(&mut self, id: Id, elem: T) -> Option<T> {
+        self.get_mut(id).map(|old| std::mem::replace(old, elem))
+    }
+    /// Overwrite an element, but give it a new identity by bumping its version. This returns the new ID of the element
+    /// The old ID becomes invalid, just like if we removed the element and added a new one at the same index
+    pub fn set(&mut self, id: Id, elem: T) -> Option<Id> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)?;
+        self.clear_tags(index);
+        let (old_val, old_version) = &mut self.slots.vec[index];
+        *old_val = Some(elem);
+        *old_version = old_version.wrapping_add(1) & self.codec.version_mask();
+        Some(self.codec.encode(index, *old_version))
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        self.slots.metrics()
+    }
+    /// Make an element unreachable right away, but keep it alive until the next call to reclaim()
+    /// Its slot gets freed immediately, so it might get re-used before the element gets reclaimed
+    pub fn mark_removed(&mut self, id: Id) -> bool {
+        match self.remove(id) {
+            Some(elem) => {
+                self.graveyard.push((id, elem));
+                true
+            }
+            None => false,
+        }
+    }
+    /// Take all the elements that were marked as removed, in the order they were marked. This is meant to be called at a safe point, like the end of a frame
+    pub fn reclaim(&mut self) -> impl Iterator<Item = (Id, T)> + '_ {
+        self.graveyard.drain(..)
+    }
+    /// Get the number of elements that were marked as removed, but that were not reclaimed yet
+    pub fn count_marked(&self) -> usize {
+        self.graveyard.len()
     }
     /// Get the number of valid elements in the ordered vector
     pub fn count(&self) -> usize {
-        self.vec.len() - self.missing.len()
+        self.slots.count()
     }
     /// Get the number of invalid elements in the ordered vector
     pub fn count_invalid(&self) -> usize {
-        self.missing.len()
+        self.slots.count_invalid()
     }
     /// Clear the whole ordered vector
     pub fn clear(&mut self) -> Vec<Option<T>> {
-        // Simple clear
-        let rep = std::mem::take(&mut self.vec);
-        self.missing.clear();
-        rep.into_iter().map(|(val, _)| val).collect::<Vec<_>>()
+        self.slots.clear()
     }
 }
 
 /// Iter magic
 impl<T> OrderedVec<T> {
     /// Convert this into an iterator
-    pub fn into_iter(self) -> impl Iterator<Item = (u64, T)> {
-        self.vec
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter(self) -> impl Iterator<Item = (Id, T)> {
+        let codec = self.codec;
+        self.slots
             .into_iter()
-            .enumerate()
-            .filter_map(|(index, (val, version))| {
-                val.map(|val| (to_id(IndexPair::new(index, version)), val))
-            })
+            .map(move |(index, version, val)| (codec.encode(index, version), val))
     }
     /// Get an iterator over the valid elements
     pub fn iter_elements(&self) -> impl Iterator<Item = &T> {
-        self.vec.iter().filter_map(|(val, _)| val.as_ref())
+        self.slots.iter_elements()
     }
     /// Get a mutable iterator over the valid elements
     pub fn iter_elements_mut(&mut self) -> impl Iterator<Item = &mut T> {
-        self.vec.iter_mut().filter_map(|(val, _)| val.as_mut())
+        self.slots.iter_elements_mut()
     }
     /// Get an iterator over the valid elements, but with the ID of each element
-    pub fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
-        self.vec
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
             .iter()
-            .enumerate()
-            .filter_map(|(index, (val, version))| {
-                val.as_ref()
-                    .map(|val| (to_id(IndexPair::new(index, *version)), val))
-            })
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
     }
     /// Get a mutable iterator over the valid elements, but with the ID of each element
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u64, &mut T)> {
-        self.vec
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let codec = self.codec;
+        self.slots
             .iter_mut()
-            .enumerate()
-            .filter_map(|(index, (val, version))| {
-                val.as_mut()
-                    .map(|val| (to_id(IndexPair::new(index, *version)), val))
-            })
+            .map(move |(index, version, val)| (codec.encode(index, version), val))
     }
     /// Get an iterator over the indices of the null elements
     pub fn iter_invalid(&self) -> impl Iterator<Item = &usize> {
-        self.missing.iter()
+        self.slots.missing.iter()
+    }
+    /// Get an iterator over the IDs that the null elements will have once push_shove() fills them back up
+    pub fn iter_vacant_ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.slots.missing.iter().map(|&index| {
+            let version = self.slots.vec[index].1.wrapping_add(1);
+            self.codec
+                .encode(index, version & self.codec.version_mask())
+        })
+    }
+    /// Copy the valid elements into a tightly packed Vec, alongside a table that maps the ID of each element to its offset inside that Vec
+    /// The elements are sorted by index, so the table is sorted by index as well
+    pub fn to_dense(&self) -> (Vec<T>, Vec<(Id, u32)>)
+    where
+        T: Clone,
+    {
+        let mut values = Vec::with_capacity(self.count());
+        let mut offsets = Vec::with_capacity(self.count());
+        for (id, val) in self.iter() {
+            offsets.push((id, values.len() as u32));
+            values.push(val.clone());
+        }
+        (values, offsets)
+    }
+    /// Consume the ordered vector into a tightly packed Vec of the valid elements, alongside the ID of each element
+    /// Both Vecs are sorted by index
+    pub fn compact_into_vec(self) -> (Vec<T>, Vec<Id>) {
+        let count = self.count();
+        let mut values = Vec::with_capacity(count);
+        let mut ids = Vec::with_capacity(count);
+        for (id, val) in self.into_iter() {
+            ids.push(id);
+            values.push(val);
+        }
+        (values, ids)
+    }
+    /// Get a cursor that walks over the valid elements, and that can remove or add elements while doing so
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut::new(self)
+    }
+    /// Start a transaction that buffers inserts, removes and updates, and that only applies them once it gets committed
+    pub fn transaction(&mut self) -> Transaction<'_, T> {
+        Transaction::new(self)
     }
     /// Drain the elements that only return true. This will return just an Iterator of the index and value of the drained elements
-    pub fn my_drain<F>(&mut self, mut filter: F) -> impl Iterator<Item = (u64, T)> + '_
+    pub fn my_drain<F>(&mut self, mut filter: F) -> impl Iterator<Item = (Id, T)> + '_
+    where
+        F: FnMut(Id, &T) -> bool,
+    {
+        // Keep track of the slots that we must remove
+        let codec = self.codec;
+        let removed = self
+            .slots
+            .filtered(|index, version, val| filter(codec.encode(index, version), val));
+        // Now we can actually remove the objects
+        removed.into_iter().map(move |(index, version)| {
+            (
+                codec.encode(index, version),
+                self.slots.remove_index(index).unwrap(),
+            )
+        })
+    }
+}
+
+/// Tags
+impl<T> OrderedVec<T> {
+    // Reset the tags of a slot that just got filled
+    fn clear_tags(&mut self, index: usize) {
+        if let Some(mask) = self.tags.get_mut(index) {
+            *mask = 0;
+        }
+    }
+    // Decode the ID with our codec, and get the index of its tag bitset if the element exists
+    fn tag_index(&self, id: Id) -> Option<usize> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)?;
+        Some(index)
+    }
+    /// Set one of the 64 tags of an element. This returns false if the element does not exist
+    pub fn set_tag(&mut self, id: Id, bit: u32) -> bool {
+        assert!(bit < 64, "There are only 64 tags per element");
+        let Some(index) = self.tag_index(id) else {
+            return false;
+        };
+        if index >= self.tags.len() {
+            self.tags.resize(index + 1, 0);
+        }
+        self.tags[index] |= 1 << bit;
+        true
+    }
+    /// Unset one of the 64 tags of an element. This returns false if the element does not exist
+    pub fn unset_tag(&mut self, id: Id, bit: u32) -> bool {
+        assert!(bit < 64, "There are only 64 tags per element");
+        let Some(index) = self.tag_index(id) else {
+            return false;
+        };
+        if let Some(mask) = self.tags.get_mut(index) {
+            *mask &= !(1 << bit);
+        }
+        true
+    }
+    /// Get the tag bitset of an element
+    pub fn tags(&self, id: Id) -> Option<u64> {
+        let index = self.tag_index(id)?;
+        Some(self.tags.get(index).copied().unwrap_or_default())
+    }
+    /// Get an iterator over the valid elements that have all the tags of the mask, but with the ID of each element
+    /// Only the tag bitsets are scanned, so the elements that don't match are skipped without touching them. An empty mask has no tag constraint, so it yields every valid element
+    pub fn iter_filtered(&self, mask: u64) -> impl Iterator<Item = (Id, &T)> {
+        // Slots past the end of the tags have no tags, so they only match the empty mask
+        let len = if mask == 0 {
+            self.slots.vec.len()
+        } else {
+            self.tags.len()
+        };
+        (0..len)
+            .filter(move |index| self.tags.get(*index).copied().unwrap_or_default() & mask == mask)
+            .filter_map(|index| {
+                let (val, version) = self.slots.vec.get(index)?;
+                Some((self.codec.encode(index, *version), val.as_ref()?))
+            })
+    }
+}
+
+/// Snapshots
+impl<T> OrderedVec<T> {
+    /// Take an immutable copy of the current contents, which can later be compared against another snapshot using diff()
+    pub fn snapshot(&self) -> Snapshot<T>
     where
-        F: FnMut(u64, &T) -> bool,
+        T: Clone,
     {
-        // Keep track of the IDs that we must remove
-        let mut removed_ids: Vec<u64> = Vec::new();
-        for (index, (val, version)) in self.vec.iter_mut().enumerate() {
-            if let Some(val) = val {
-                // If it validates the filter, we must remove it
-                let id = to_id(IndexPair::new(index, *version));
-                if filter(id, val) {
-                    // We must remove this value
-                    removed_ids.push(id);
+        Snapshot {
+            slots: self.slots.clone(),
+            codec: self.codec,
+        }
+    }
+    /// Apply the changes of a patch, keeping the IDs that they contain
+    /// This returns false if some of the removes or updates did not match up with our elements, in which case they get skipped
+    /// Patches don't contain the order of the free list, so the free slots might get re-used in a different order than in the original, and get_next_id() might not match up
+    pub fn apply_patch(&mut self, patch: Patch<T>) -> bool {
+        self.apply_ops(patch.changes)
+    }
+    /// Replay a list of changes in order, keeping the IDs that they contain. Replaying the changes recorded by a Mirror onto a fresh ordered vector rebuilds the exact same elements and IDs
+    /// This returns false if some of the removes or updates did not match up with our elements, in which case they get skipped
+    /// Inserts with a null or foreign ID, or that would add more than MAX_REPLAY_GAP vacant slots, get skipped as well
+    pub fn apply_ops(&mut self, ops: impl IntoIterator<Item = Change<T>>) -> bool {
+        let mut matched = true;
+        for change in ops {
+            match change {
+                Change::Insert(id, elem) => {
+                    // The changes are external input, so foreign IDs get skipped instead of panicking like they do in checked_decode()
+                    if id.is_null() || !self.codec.owns(id) {
+                        matched = false;
+                        continue;
+                    }
+                    let (index, version) = self.codec.decode(id);
+                    if index > self.slots.vec.len() + MAX_REPLAY_GAP {
+                        matched = false;
+                        continue;
+                    }
+                    self.slots.insert_at(index, version, elem);
+                    self.clear_tags(index);
                 }
+                Change::Remove(id) => matched &= self.remove(id).is_some(),
+                Change::Update(id, elem) => match self.get_mut(id) {
+                    Some(old) => *old = elem,
+                    None => matched = false,
+                },
             }
         }
-        // Now we can actually remove the objects
-        removed_ids
-            .into_iter()
-            .map(|id| (id, self.remove(id).unwrap()))
+        matched
+    }
+}
+
+/// Typed handles
+#[cfg(feature = "typed-handles")]
+impl<T> OrderedVec<T> {
+    /// Add an element to the ordered vector, and return a handle that can only be used with an ordered vector of the same type
+    pub fn push_handle(&mut self, elem: T) -> Handle<T> {
+        Handle::from_id(self.push_shove(elem))
+    }
+    /// Get a reference to an element using its typed handle
+    pub fn get_handle(&self, handle: Handle<T>) -> Option<&T> {
+        self.get(handle.id())
+    }
+    /// Get a mutable reference to an element using its typed handle
+    pub fn get_handle_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.get_mut(handle.id())
+    }
+    /// Remove an element using its typed handle
+    pub fn remove_handle(&mut self, handle: Handle<T>) -> Option<T> {
+        self.remove(handle.id())
+    }
+    /// Get an iterator over the valid elements, but with the typed handle of each element
+    pub fn iter_handles(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.iter().map(|(id, val)| (Handle::from_id(id), val))
+    }
+}
+
+/// Conversions
+impl<T> OrderedVec<T> {
+    // Put each element at exactly the index and version of its raw ID. If two elements share the same index, the last one wins
+    fn from_raw_ids(elems: impl IntoIterator<Item = (u64, T)>) -> Self {
+        let mut vec = Self::default();
+        for (id, elem) in elems {
+            if let Some((index, version)) = vec.codec.checked_decode(Id::from(id)) {
+                vec.slots.insert_at(index, version, elem);
+            }
+        }
+        vec
+    }
+}
+
+impl<T> From<Vec<(u64, T)>> for OrderedVec<T> {
+    fn from(elems: Vec<(u64, T)>) -> Self {
+        Self::from_raw_ids(elems)
+    }
+}
+
+impl<T> From<HashMap<u64, T>> for OrderedVec<T> {
+    fn from(elems: HashMap<u64, T>) -> Self {
+        Self::from_raw_ids(elems)
+    }
+}
+
+impl<T> From<OrderedVec<T>> for Vec<(u64, T)> {
+    fn from(vec: OrderedVec<T>) -> Self {
+        vec.into_iter()
+            .map(|(id, elem)| (id.to_u64(), elem))
+            .collect()
+    }
+}
+
+impl<T> From<OrderedVec<T>> for HashMap<u64, T> {
+    fn from(vec: OrderedVec<T>) -> Self {
+        vec.into_iter()
+            .map(|(id, elem)| (id.to_u64(), elem))
+            .collect()
     }
 }
 
 /// Traits
-impl<T> Index<u64> for OrderedVec<T> {
+impl<T> Index<Id> for OrderedVec<T> {
     type Output = T;
-    fn index(&self, index: u64) -> &Self::Output {
+    fn index(&self, index: Id) -> &Self::Output {
         self.get(index).unwrap()
     }
 }
 
-impl<T> IndexMut<u64> for OrderedVec<T> {
-    fn index_mut(&mut self, index: u64) -> &mut Self::Output {
+impl<T> IndexMut<Id> for OrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
         self.get_mut(index).unwrap()
     }
 }
+
+#[cfg(feature = "typed-handles")]
+impl<T> Index<Handle<T>> for OrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Handle<T>) -> &Self::Output {
+        self.get_handle(index).unwrap()
+    }
+}
+
+#[cfg(feature = "typed-handles")]
+impl<T> IndexMut<Handle<T>> for OrderedVec<T> {
+    fn index_mut(&mut self, index: Handle<T>) -> &mut Self::Output {
+        self.get_handle_mut(index).unwrap()
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::{check_len, InvariantError},
+    metrics::Metrics,
+    slots::{SlotStorage, SlotStorageMut, Slots},
+    utils::{Id, IdCodec, ReusePolicy},
+};
+
+/// The number of slots stored inside each page
+pub const PAGE_SIZE: usize = 1024;
+
+/// The slot storage of the paged ordered vector. Each page is allocated with a capacity of PAGE_SIZE and never grows past it
+/// The slot of an index is at index % PAGE_SIZE inside page index / PAGE_SIZE
+pub(crate) struct Pages<T> {
+    /// The pages of slots, containing the elements and their versions
+    pub(crate) pages: Vec<Vec<(Option<T>, u32)>>,
+    /// The total number of slots
+    pub(crate) len: usize,
+}
+
+impl<T: Clone> Clone for Pages<T> {
+    fn clone(&self) -> Self {
+        // Keep the capacity of the pages, so they don't reallocate when we push to them
+        let pages = self
+            .pages
+            .iter()
+            .map(|page| {
+                let mut copy = Vec::with_capacity(PAGE_SIZE);
+                copy.extend(page.iter().cloned());
+                copy
+            })
+            .collect();
+        Self {
+            pages,
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for Pages<T> {
+    fn default() -> Self {
+        Self {
+            pages: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> SlotStorage<T, u32> for Pages<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn slot(&self, index: usize) -> Option<&(Option<T>, u32)> {
+        self.pages.get(index / PAGE_SIZE)?.get(index % PAGE_SIZE)
+    }
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a (Option<T>, u32)>
+    where
+        T: 'a,
+    {
+        self.pages.iter().flatten()
+    }
+    fn bytes_allocated(&self) -> usize {
+        self.pages.capacity() * std::mem::size_of::<Vec<(Option<T>, u32)>>()
+            + self.pages.len() * PAGE_SIZE * std::mem::size_of::<(Option<T>, u32)>()
+    }
+}
+
+impl<T> SlotStorageMut<T, u32> for Pages<T> {
+    fn slot_mut(&mut self, index: usize) -> Option<&mut (Option<T>, u32)> {
+        self.pages
+            .get_mut(index / PAGE_SIZE)?
+            .get_mut(index % PAGE_SIZE)
+    }
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut (Option<T>, u32)>
+    where
+        T: 'a,
+    {
+        self.pages.iter_mut().flatten()
+    }
+    fn push(&mut self, slot: (Option<T>, u32)) -> Result<(), (Option<T>, u32)> {
+        // Add a new page if the last one is full
+        if self.len.is_multiple_of(PAGE_SIZE) {
+            self.pages.push(Vec::with_capacity(PAGE_SIZE));
+        }
+        self.pages.last_mut().unwrap().push(slot);
+        self.len += 1;
+        Ok(())
+    }
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// An ordered vector that stores its slots in fixed-size pages instead of one big Vec
+/// Growing only allocates a new page, so we never copy the old elements around, and their addresses stay the same forever
+/// The slot of an ID is at index % PAGE_SIZE inside page index / PAGE_SIZE
+pub struct PagedOrderedVec<T> {
+    /// The slots containing the elements and their versions, stored in pages, with their free list
+    pub(crate) slots: Slots<T, u32, Pages<T>>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+}
+
+impl<T> Clone for PagedOrderedVec<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Debug for PagedOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PagedOrderedVec")
+            .field("pages", &self.page_count())
+            .field("vec", &self.slots.vec.iter().collect::<Vec<_>>())
+            .field("missing", &self.slots.missing)
+            .finish()
+    }
+}
+
+impl<T> Default for PagedOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            slots: Slots::default(),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Page magic
+impl<T> PagedOrderedVec<T> {
+    /// Get the number of pages that we have allocated
+    pub fn page_count(&self) -> usize {
+        self.slots.vec.pages.len()
+    }
+}
+
+/// Actual code
+impl<T> PagedOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Create a new ordered vector that re-uses its free slots using a specific policy
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            slots: Slots::with_policy(policy),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Add an element to the ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let (index, version) = self.slots.push_shove(elem);
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec.slot_mut(index).unwrap().1 = version;
+        self.codec.encode(index, version)
+    }
+    /// Get the ID of the next element that we will add
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.remove(index, version)
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
+    }
+    /// Get a mutable reference to an element in the ordered vector
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get_mut(index, version)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        let pages = &self.slots.vec.pages;
+        check_len(
+            "pages",
+            self.slots.vec.len,
+            pages.iter().map(|page| page.len()).sum(),
+        )?;
+        self.slots.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        self.slots.metrics()
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the number of invalid elements in the ordered vector
+    pub fn count_invalid(&self) -> usize {
+        self.slots.count_invalid()
+    }
+    /// Clear the whole ordered vector, freeing all of its pages
+    pub fn clear(&mut self) -> Vec<Option<T>> {
+        self.slots.clear()
+    }
+}
+
+/// Iter magic
+impl<T> PagedOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let codec = self.codec;
+        self.slots
+            .iter_mut()
+            .map(move |(index, version, val)| (codec.encode(index, version), val))
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for PagedOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> IndexMut<Id> for PagedOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    slots::Slots,
+    utils::{Id, IdCodec},
+};
+
+/// The maximum number of vacant slots that a replayed insert can add in front of its own slot
+/// Replayed changes might come from the network, so this stops a garbage ID from growing the ordered vector to billions of slots
+pub const MAX_REPLAY_GAP: usize = 1 << 16;
+
+/// An immutable copy of the contents of an OrderedVec at some point in time
+pub struct Snapshot<T> {
+    /// The copied slots
+    pub(crate) slots: Slots<T, u32>,
+    /// The codec of the ordered vector that we copied
+    pub(crate) codec: IdCodec,
+}
+
+impl<T> Clone for Snapshot<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Snapshot<T> {
+    /// Get a reference to an element in the snapshot
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
+    }
+    /// Get the number of valid elements in the snapshot
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+}
+
+/// A single change between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<T> {
+    /// A new element was added with this ID
+    Insert(Id, T),
+    /// The element with this ID was removed
+    Remove(Id),
+    /// The element with this ID got a new value
+    Update(Id, T),
+}
+
+/// A list of changes that turns one snapshot into another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch<T> {
+    /// The changes, sorted by index
+    pub changes: Vec<Change<T>>,
+}
+
+impl<T> Patch<T> {
+    /// Check if this patch doesn't change anything
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Compare two snapshots of the same ordered vector, and list the changes that turn the old one into the new one
+pub fn diff<T: Clone + PartialEq>(old: &Snapshot<T>, new: &Snapshot<T>) -> Patch<T> {
+    let mut changes = Vec::new();
+    let len = old.slots.vec.len().max(new.slots.vec.len());
+    for index in 0..len {
+        let before = old
+            .slots
+            .vec
+            .get(index)
+            .and_then(|(val, version)| Some((val.as_ref()?, *version)));
+        let after = new
+            .slots
+            .vec
+            .get(index)
+            .and_then(|(val, version)| Some((val.as_ref()?, *version)));
+        match (before, after) {
+            // Same element, but it might have a new value
+            (Some((a, v1)), Some((b, v2))) if v1 == v2 => {
+                if a != b {
+                    changes.push(Change::Update(new.codec.encode(index, v2), b.clone()));
+                }
+            }
+            // The slot got re-used
+            (Some((_, v1)), Some((b, v2))) => {
+                changes.push(Change::Remove(old.codec.encode(index, v1)));
+                changes.push(Change::Insert(new.codec.encode(index, v2), b.clone()));
+            }
+            (Some((_, v1)), None) => changes.push(Change::Remove(old.codec.encode(index, v1))),
+            (None, Some((b, v2))) => {
+                changes.push(Change::Insert(new.codec.encode(index, v2), b.clone()))
+            }
+            (None, None) => {}
+        }
+    }
+    Patch { changes }
+}
@@ -0,0 +1,152 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::{Deref, Index},
+    sync::Arc,
+};
+
+use crate::{invariant::InvariantError, metrics::Metrics, ordered_vec::OrderedVec, utils::Id};
+
+/// The error returned when we try to remove an element that is still pinned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pinned(pub Id);
+
+impl Display for Pinned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Element {} is still pinned", self.0)
+    }
+}
+
+impl std::error::Error for Pinned {}
+
+/// A guard that keeps an element of a PinnedOrderedVec alive and readable, even if the vector gets modified in the meantime
+/// As long as a guard exists, the element cannot be removed normally
+pub struct PinnedRef<T> {
+    /// The ID of the pinned element
+    id: Id,
+    /// The pinned element
+    value: Arc<T>,
+}
+
+impl<T> PinnedRef<T> {
+    /// Get the ID of the pinned element
+    pub fn id(&self) -> Id {
+        self.id
+    }
+}
+
+impl<T> Clone for PinnedRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: Debug> Debug for PinnedRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PinnedRef")
+            .field("id", &self.id)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<T> Deref for PinnedRef<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+/// An ordered vector whose elements can be pinned, which stops them from getting removed while someone is still using them
+/// Each element is stored behind an Arc, and each pin is a clone of that Arc, so the pin count is just the strong count minus one
+pub struct PinnedOrderedVec<T> {
+    /// The elements
+    pub(crate) vec: OrderedVec<Arc<T>>,
+}
+
+impl<T: Debug> Debug for PinnedOrderedVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PinnedOrderedVec")
+            .field("vec", &self.vec)
+            .finish()
+    }
+}
+
+impl<T> Default for PinnedOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            vec: OrderedVec::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<T> PinnedOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add an element to the ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        self.vec.push_shove(Arc::new(elem))
+    }
+    /// Pin an element, so it can't be removed until the returned guard (and all of its clones) get dropped
+    pub fn pin(&self, id: Id) -> Option<PinnedRef<T>> {
+        let value = self.vec.get(id)?.clone();
+        Some(PinnedRef { id, value })
+    }
+    /// Get the number of guards that currently pin an element
+    pub fn pin_count(&self, id: Id) -> Option<usize> {
+        self.vec.get(id).map(|value| Arc::strong_count(value) - 1)
+    }
+    /// Remove an element. This fails if the element is still pinned
+    pub fn remove(&mut self, id: Id) -> Result<Option<T>, Pinned> {
+        match self.pin_count(id) {
+            None => Ok(None),
+            Some(0) => Ok(Arc::into_inner(self.vec.remove(id).unwrap())),
+            Some(_) => Err(Pinned(id)),
+        }
+    }
+    /// Remove an element, even if it is pinned. The element becomes unreachable right away, but it only gets dropped once all the guards are dropped
+    pub fn remove_deferred(&mut self, id: Id) -> bool {
+        self.vec.remove(id).is_some()
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.vec.get(id).map(|value| value.as_ref())
+    }
+    /// Get a mutable reference to an element in the ordered vector. This fails if the element is pinned, since the guards might be reading it
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        Arc::get_mut(self.vec.get_mut(id)?)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        self.vec.metrics()
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.vec.count()
+    }
+}
+
+/// Iter magic
+impl<T> PinnedOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.vec.iter().map(|(id, value)| (id, value.as_ref()))
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for PinnedOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{
+    invariant::InvariantError, metrics::Metrics, shareable::ShareableOrderedVec,
+    simple::OrderedVec, utils::Id,
+};
+
+/// An object pool built on top of an ordered vector
+/// Acquiring an element returns a guard that owns it, and dropping that guard frees up its slot automatically
+/// The pool is a shared handle, so cloning it gives another handle to the same pool, and any number of guards can be alive at the same time
+/// While an element is acquired, its slot is kept occupied but the element lives inside its guard, so get() only sees the elements that were kept
+pub struct Pool<T> {
+    /// The underlying ordered vector. The slots of the acquired elements are empty until they get kept
+    pub(crate) vec: Rc<RefCell<OrderedVec<Option<T>>>>,
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            vec: self.vec.clone(),
+        }
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self {
+            vec: Rc::new(RefCell::new(OrderedVec::default())),
+        }
+    }
+}
+
+impl<T> Debug for Pool<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pool").field("vec", &self.vec).finish()
+    }
+}
+
+impl<T> Pool<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add an element to the pool, and return a guard that will remove it when dropped
+    pub fn acquire(&self, elem: T) -> PoolGuard<T> {
+        let id = self.vec.borrow_mut().push_shove(None);
+        PoolGuard {
+            vec: self.vec.clone(),
+            id,
+            elem: Some(elem),
+        }
+    }
+    /// Add an element created by the given function to the pool, and return a guard that will remove it when dropped
+    pub fn acquire_with(&self, f: impl FnOnce() -> T) -> PoolGuard<T> {
+        self.acquire(f())
+    }
+    /// Remove an element that was kept alive using [`PoolGuard::keep`]
+    pub fn release(&self, id: Id) -> Option<T> {
+        let mut vec = self.vec.borrow_mut();
+        vec.get(id)?.as_ref()?;
+        vec.remove(id)?
+    }
+    /// Get a reference to an element that was kept in the pool
+    pub fn get(&self, id: Id) -> Option<Ref<'_, T>> {
+        Ref::filter_map(self.vec.borrow(), |vec| vec.get(id)?.as_ref()).ok()
+    }
+    /// Get a mutable reference to an element that was kept in the pool
+    pub fn get_mut(&self, id: Id) -> Option<RefMut<'_, T>> {
+        RefMut::filter_map(self.vec.borrow_mut(), |vec| vec.get_mut(id)?.as_mut()).ok()
+    }
+    /// Get the number of elements currently acquired, including the ones that were kept
+    pub fn count(&self) -> usize {
+        self.vec.borrow().count()
+    }
+    /// Check the structural invariants of the pool, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.vec.borrow().validate()
+    }
+    /// Get the memory and fragmentation statistics of the pool
+    pub fn metrics(&self) -> Metrics {
+        self.vec.borrow().metrics()
+    }
+    /// Get the underlying ordered vector. The elements that are currently inside a guard are None
+    pub fn inner(&self) -> Ref<'_, OrderedVec<Option<T>>> {
+        self.vec.borrow()
+    }
+}
+
+/// A guard that owns an acquired element, and returns its slot to the pool when dropped
+pub struct PoolGuard<T> {
+    /// The pool that we acquired the element from
+    vec: Rc<RefCell<OrderedVec<Option<T>>>>,
+    /// The ID of the acquired element
+    id: Id,
+    /// The acquired element itself. This only gets taken when the guard is consumed
+    elem: Option<T>,
+}
+
+impl<T> PoolGuard<T> {
+    /// Get the ID of the acquired element
+    pub fn id(&self) -> Id {
+        self.id
+    }
+    /// Keep the element in the pool, even after the guard is gone. It must be released manually using [`Pool::release`]
+    pub fn keep(mut self) -> Id {
+        let elem = self.elem.take();
+        *self.vec.borrow_mut().get_mut(self.id).unwrap() = elem;
+        self.id
+    }
+    /// Remove the element from the pool right now, returning it
+    pub fn take(mut self) -> T {
+        self.vec.borrow_mut().remove(self.id);
+        self.elem.take().unwrap()
+    }
+}
+
+impl<T> Deref for PoolGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.elem.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for PoolGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.elem.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for PoolGuard<T> {
+    fn drop(&mut self) {
+        // Return the slot to the pool, unless the element was kept or taken already
+        if self.elem.is_some() {
+            self.vec.borrow_mut().remove(self.id);
+        }
+    }
+}
+
+/// An object pool built on top of a shareable ordered vector, that can be used from multiple threads at the same time
+/// Acquiring an element only reserves its slot, so multiple threads can acquire elements in parallel while holding the read lock
+/// The element lives inside its guard, and dropping that guard frees up its slot automatically
+pub struct SharedPool<T> {
+    /// The underlying shareable ordered vector. The slots of the acquired elements stay reserved until they get kept
+    pub(crate) vec: Arc<RwLock<ShareableOrderedVec<T>>>,
+}
+
+impl<T> Clone for SharedPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            vec: self.vec.clone(),
+        }
+    }
+}
+
+impl<T> Default for SharedPool<T> {
+    fn default() -> Self {
+        Self {
+            vec: Arc::new(RwLock::new(ShareableOrderedVec::default())),
+        }
+    }
+}
+
+impl<T> Debug for SharedPool<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedPool")
+            .field("vec", &self.vec)
+            .finish()
+    }
+}
+
+impl<T> SharedPool<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // A panic while holding the lock can't break the ordered vector, so we ignore the poisoning
+    fn read(&self) -> RwLockReadGuard<'_, ShareableOrderedVec<T>> {
+        self.vec.read().unwrap_or_else(PoisonError::into_inner)
+    }
+    // Lock the ordered vector for writing
+    fn write(&self) -> RwLockWriteGuard<'_, ShareableOrderedVec<T>> {
+        self.vec.write().unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Add an element to the pool, and return a guard that will remove it when dropped
+    pub fn acquire(&self, elem: T) -> SharedPoolGuard<T> {
+        let id = self.read().get_next_id_increment();
+        SharedPoolGuard {
+            pool: self.clone(),
+            id,
+            elem: Some(elem),
+        }
+    }
+    /// Add an element created by the given function to the pool, and return a guard that will remove it when dropped
+    pub fn acquire_with(&self, f: impl FnOnce() -> T) -> SharedPoolGuard<T> {
+        self.acquire(f())
+    }
+    // Give a reserved slot back to the free list. The only way to do that is to fill it, and then remove the element right away
+    fn free(&self, id: Id, elem: T) -> T {
+        let mut vec = self.write();
+        vec.insert(id, elem).unwrap();
+        vec.remove(id).unwrap()
+    }
+    /// Remove an element that was kept alive using [`SharedPoolGuard::keep`]
+    pub fn release(&self, id: Id) -> Option<T> {
+        self.write().remove(id)
+    }
+    /// Get the number of elements that were kept in the pool
+    pub fn count(&self) -> usize {
+        self.read().count()
+    }
+    /// Get the number of elements that are currently inside a guard
+    pub fn count_acquired(&self) -> usize {
+        self.read().count_reserved()
+    }
+    /// Check the structural invariants of the pool, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.read().validate()
+    }
+    /// Get the memory and fragmentation statistics of the pool
+    pub fn metrics(&self) -> Metrics {
+        self.read().metrics()
+    }
+    /// Lock the underlying shareable ordered vector for reading, to fetch the elements that were kept
+    pub fn inner(&self) -> RwLockReadGuard<'_, ShareableOrderedVec<T>> {
+        self.read()
+    }
+}
+
+/// A guard that owns an element acquired from a shared pool, and returns its slot to the pool when dropped
+pub struct SharedPoolGuard<T> {
+    /// The pool that we acquired the element from
+    pool: SharedPool<T>,
+    /// The ID that was reserved for the acquired element
+    id: Id,
+    /// The acquired element itself. This only gets taken when the guard is consumed
+    elem: Option<T>,
+}
+
+impl<T> SharedPoolGuard<T> {
+    /// Get the ID of the acquired element
+    pub fn id(&self) -> Id {
+        self.id
+    }
+    /// Keep the element in the pool, even after the guard is gone. It must be released manually using [`SharedPool::release`]
+    pub fn keep(mut self) -> Id {
+        let elem = self.elem.take().unwrap();
+        // The ID was reserved by us, so it is always valid
+        self.pool.write().insert(self.id, elem).unwrap();
+        self.id
+    }
+    /// Remove the element from the pool right now, returning it
+    pub fn take(mut self) -> T {
+        let elem = self.elem.take().unwrap();
+        self.pool.free(self.id, elem)
+    }
+}
+
+impl<T> Deref for SharedPoolGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.elem.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for SharedPoolGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.elem.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for SharedPoolGuard<T> {
+    fn drop(&mut self) {
+        // Return the reserved slot to the pool, unless the element was kept or taken already
+        if let Some(elem) = self.elem.take() {
+            self.pool.free(self.id, elem);
+        }
+    }
+}
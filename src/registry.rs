@@ -0,0 +1,85 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+};
+
+use crate::{simple::OrderedVec, utils::Id};
+
+/// A registry containing one ordered vector per element type
+/// This is basically a component storage, where the type of the element is the key to fetch the storage
+#[derive(Default)]
+pub struct Registry {
+    /// Each boxed value is an OrderedVec<T>, where T is the type of the key
+    pub(crate) storages: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("storages", &self.storages.len())
+            .finish()
+    }
+}
+
+impl Registry {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Register a new element type. This does nothing if the type was already registered
+    pub fn register<T: Any + Send>(&mut self) -> &mut OrderedVec<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(OrderedVec::<T>::new()))
+            .downcast_mut::<OrderedVec<T>>()
+            .unwrap()
+    }
+    /// Check if an element type was registered
+    pub fn is_registered<T: Any + Send>(&self) -> bool {
+        self.storages.contains_key(&TypeId::of::<T>())
+    }
+    /// Get the ordered vector that stores the elements of type T
+    pub fn storage<T: Any + Send>(&self) -> Option<&OrderedVec<T>> {
+        self.storages.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+    /// Get the ordered vector that stores the elements of type T mutably
+    pub fn storage_mut<T: Any + Send>(&mut self) -> Option<&mut OrderedVec<T>> {
+        self.storages.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+    /// Add an element to the storage of its type. This will register the type if needed
+    pub fn push<T: Any + Send>(&mut self, elem: T) -> Id {
+        self.register::<T>().push_shove(elem)
+    }
+    /// Get a reference to an element of type T
+    pub fn get<T: Any + Send>(&self, id: Id) -> Option<&T> {
+        self.storage::<T>()?.get(id)
+    }
+    /// Get a mutable reference to an element of type T
+    pub fn get_mut<T: Any + Send>(&mut self, id: Id) -> Option<&mut T> {
+        self.storage_mut::<T>()?.get_mut(id)
+    }
+    /// Remove an element of type T
+    pub fn remove<T: Any + Send>(&mut self, id: Id) -> Option<T> {
+        self.storage_mut::<T>()?.remove(id)
+    }
+    /// Remove the storage of type T completely, returning it
+    pub fn unregister<T: Any + Send>(&mut self) -> Option<OrderedVec<T>> {
+        let boxed = self.storages.remove(&TypeId::of::<T>())?;
+        Some(*boxed.downcast::<OrderedVec<T>>().unwrap())
+    }
+}
+
+/// Iter magic
+impl Registry {
+    /// Get an iterator over the elements of type T, with the ID of each element
+    pub fn iter<T: Any + Send>(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.storage::<T>().into_iter().flat_map(|vec| vec.iter())
+    }
+    /// Get a mutable iterator over the elements of type T, with the ID of each element
+    pub fn iter_mut<T: Any + Send>(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        self.storage_mut::<T>()
+            .into_iter()
+            .flat_map(|vec| vec.iter_mut())
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    ordered_vec::OrderedVec,
+    utils::{Id, IdCodec},
+};
+
+/// A collection that stores extra data for the IDs handed out by a primary OrderedVec
+/// This does not allocate any IDs by itself, it just uses the index of each ID to find its slot, and the version to check if the data is still valid
+pub struct SecondaryOrderedVec<U> {
+    /// The slots containing the data and the version of the ID that it was inserted with
+    pub(crate) vec: Vec<Option<(u32, U)>>,
+    /// The codec of the primary ordered vector, used to unpack its IDs
+    pub(crate) codec: IdCodec,
+}
+
+impl<U> Clone for SecondaryOrderedVec<U>
+where
+    U: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            vec: self.vec.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<U> Debug for SecondaryOrderedVec<U>
+where
+    U: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecondaryOrderedVec")
+            .field("vec", &self.vec)
+            .finish()
+    }
+}
+
+impl<U> Default for SecondaryOrderedVec<U> {
+    fn default() -> Self {
+        Self {
+            vec: Vec::new(),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<U> SecondaryOrderedVec<U> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new secondary ordered vector that understands the IDs of a specific primary ordered vector
+    pub fn for_primary<T>(primary: &OrderedVec<T>) -> Self {
+        Self {
+            vec: Vec::new(),
+            codec: primary.codec(),
+        }
+    }
+    /// Insert some data for an ID. If the slot contained data for an older ID, it gets overwritten
+    /// This returns the old data only if it was inserted with the same ID
+    pub fn insert(&mut self, id: Id, data: U) -> Option<U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        if index >= self.vec.len() {
+            self.vec.resize_with(index + 1, || None);
+        }
+        let old = self.vec[index].replace((version, data))?;
+        (old.0 == version).then_some(old.1)
+    }
+    /// Remove the data of an ID
+    pub fn remove(&mut self, id: Id) -> Option<U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let slot = self.vec.get_mut(index)?;
+        if slot.as_ref()?.0 != version {
+            return None;
+        }
+        slot.take().map(|(_, data)| data)
+    }
+    /// Get a reference to the data of an ID
+    pub fn get(&self, id: Id) -> Option<&U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        match self.vec.get(index)? {
+            Some((stored, data)) if *stored == version => Some(data),
+            _ => None,
+        }
+    }
+    /// Get a mutable reference to the data of an ID
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        match self.vec.get_mut(index)? {
+            Some((stored, data)) if *stored == version => Some(data),
+            _ => None,
+        }
+    }
+    /// Check if we have some data for an ID
+    pub fn contains(&self, id: Id) -> bool {
+        self.get(id).is_some()
+    }
+    /// Get the number of IDs that have some data. This might include data of IDs that were removed from the primary
+    pub fn count(&self) -> usize {
+        self.vec.iter().filter(|x| x.is_some()).count()
+    }
+    /// Clear the whole secondary ordered vector
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+}
+
+/// Iter magic
+impl<U> SecondaryOrderedVec<U> {
+    /// Get an iterator over the data, with the ID that it was inserted with
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &U)> {
+        let codec = self.codec;
+        self.vec
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, slot)| {
+                let (version, data) = slot.as_ref()?;
+                Some((codec.encode(index, *version), data))
+            })
+    }
+    /// Get a mutable iterator over the data, with the ID that it was inserted with
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut U)> {
+        let codec = self.codec;
+        self.vec
+            .iter_mut()
+            .enumerate()
+            .filter_map(move |(index, slot)| {
+                let (version, data) = slot.as_mut()?;
+                Some((codec.encode(index, *version), data))
+            })
+    }
+}
+
+/// Traits
+impl<U> Index<Id> for SecondaryOrderedVec<U> {
+    type Output = U;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<U> IndexMut<Id> for SecondaryOrderedVec<U> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
@@ -1,33 +1,202 @@
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display},
     ops::{Index, IndexMut},
-    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    sync::{
+        atomic::{
+            AtomicU32, AtomicU64,
+            Ordering::{AcqRel, Acquire, Relaxed},
+        },
+        Arc,
+    },
 };
 
-use crate::utils::{from_id, to_id, IndexPair};
+use crate::{
+    invariant::{check_len, debug_validate, InvariantError},
+    metrics::Metrics,
+    slots::{SlotVersion, Slots, UNINITIALIZED},
+    utils::{GrowthPolicy, Id, IdCodec},
+};
+
+/// The error returned when we strictly insert an element using an ID that was never handed out by get_next_id_increment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unreserved(pub Id);
+
+impl Display for Unreserved {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} was never reserved", self.0)
+    }
+}
+
+impl std::error::Error for Unreserved {}
+
+/// The error returned when we insert an element using an ID that is null, that belongs to another collection, or whose index does not fit inside the free list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidId(pub Id);
+
+impl Display for InvalidId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is not a valid ID for this collection", self.0)
+    }
+}
+
+impl std::error::Error for InvalidId {}
+
+// The end of the free list
+const END: u32 = u32::MAX;
+// The link of a free slot that was popped by get_next_id_increment, but that was not inserted into yet
+const RESERVED: u32 = u32::MAX - 1;
+
+// Pack the top of the free list and the length into a single reservation state
+fn pack(head: u32, length: u32) -> u64 {
+    (u64::from(length) << 32) | u64::from(head)
+}
+
+// Unpack a reservation state into the top of the free list and the length
+fn unpack(state: u64) -> (u32, u32) {
+    (state as u32, (state >> 32) as u32)
+}
+
+/// The state that other threads touch when reserving IDs. This is a lock-free stack of the free slots that were not reserved yet, linked through their indices, and the length of the vector including the reserved slots
+/// The top of the stack and the length live inside a single atomic, so a reservation either pops a free slot or grows the length in one step
+/// Other threads can only reserve, and the owner thread can only push through a mutable reference, so a popped slot never comes back while another thread is popping
+pub(crate) struct Reservations {
+    /// The index of the free slot that gets reserved next in the lower half, and the length in the upper half
+    state: AtomicU64,
+    /// For each slot, the index of the free slot below it, or RESERVED if it got popped
+    next: Vec<AtomicU32>,
+}
+
+impl Reservations {
+    // Create an empty free list, with a length of zero
+    pub(crate) fn new() -> Self {
+        Self {
+            state: AtomicU64::new(pack(END, 0)),
+            next: Vec::new(),
+        }
+    }
+    // Get the length of the vector, including the slots that were reserved but not inserted yet
+    fn length(&self) -> usize {
+        unpack(self.state.load(Acquire)).1 as usize
+    }
+    // Make sure the length covers a specific number of slots
+    fn cover(&mut self, len: usize) {
+        let (head, length) = unpack(*self.state.get_mut());
+        *self.state.get_mut() = pack(head, length.max(Self::narrow(len)));
+    }
+    // Get the index that gets reserved next, without reserving it
+    fn peek(&self) -> usize {
+        match unpack(self.state.load(Acquire)) {
+            (END, length) => length as usize,
+            (head, _) => head as usize,
+        }
+    }
+    // Reserve an index, by either popping the top free slot or growing the length. This can be called from any thread
+    fn reserve(&self) -> usize {
+        let mut state = self.state.load(Acquire);
+        loop {
+            let (head, length) = unpack(state);
+            let next = match head {
+                END => pack(END, Self::narrow(length as usize + 1)),
+                head => pack(self.next[head as usize].load(Relaxed), length),
+            };
+            match self
+                .state
+                .compare_exchange_weak(state, next, AcqRel, Acquire)
+            {
+                Ok(_) if head == END => return length as usize,
+                Ok(_) => {
+                    // Only the thread that won the slot gets to mark it
+                    self.next[head as usize].store(RESERVED, Relaxed);
+                    return head as usize;
+                }
+                Err(current) => state = current,
+            }
+        }
+    }
+    // Put a slot on top of the free list
+    fn push(&mut self, index: usize) {
+        if index >= self.next.len() {
+            self.next
+                .resize_with(index + 1, || AtomicU32::new(RESERVED));
+        }
+        let (head, length) = unpack(*self.state.get_mut());
+        *self.next[index].get_mut() = head;
+        *self.state.get_mut() = pack(Self::narrow(index), length);
+    }
+    // Take a slot out of the free list if it was never reserved, like when it gets filled using a fabricated ID
+    fn unlink(&mut self, index: usize) {
+        match self.next.get_mut(index).map(|next| *next.get_mut()) {
+            Some(RESERVED) | None => return,
+            Some(_) => {}
+        }
+        // Walk down the list, remembering the slot that links to the current one
+        let below = *self.next[index].get_mut();
+        let (head, length) = unpack(*self.state.get_mut());
+        let mut above: Option<usize> = None;
+        let mut current = head;
+        while current != END {
+            if current as usize == index {
+                match above {
+                    Some(above) => *self.next[above].get_mut() = below,
+                    None => *self.state.get_mut() = pack(below, length),
+                }
+                *self.next[index].get_mut() = RESERVED;
+                return;
+            }
+            above = Some(current as usize);
+            current = *self.next[current as usize].get_mut();
+        }
+    }
+    // Check if a free slot was popped but not inserted into yet
+    fn is_reserved(&self, index: usize) -> bool {
+        self.next
+            .get(index)
+            .is_none_or(|next| next.load(Relaxed) == RESERVED)
+    }
+    // Iterate over the free slots that were not reserved yet, from top to bottom
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut link = unpack(self.state.load(Acquire)).0;
+        std::iter::from_fn(move || {
+            let current = link;
+            if current == END || current == RESERVED {
+                return None;
+            }
+            link = self.next.get(current as usize)?.load(Relaxed);
+            Some(current as usize)
+        })
+    }
+    // Convert an index into one half of the reservation state
+    fn narrow(index: usize) -> u32 {
+        u32::try_from(index)
+            .ok()
+            .filter(|index| *index < RESERVED)
+            .expect("A shareable ordered vector can only reserve up to 2^32 - 2 slots")
+    }
+}
+
 /// A collection that keeps the ordering of its elements, even when deleting an element
 /// However, this collection can be shared between threads
 /// We can *guess* what the index is for an element that we must add
 /// We can use **get**, and **get_next_idx_increment** on other threads, but that is all
 /// We must do the rest of our operations using an external messaging system
 pub struct ShareableOrderedVec<T> {
-    /// A list of the current elements in the list
-    pub(crate) vec: Vec<(Option<T>, Option<u32>)>,
-    /// A list of the indices that contain a null element, so whenever we add a new element, we will add it there
-    pub(crate) missing: Vec<usize>,
-    /// A counter that increases every time we add an element to the list in other threads, before the main update
-    pub(crate) counter: AtomicUsize,
-    /// The current length of the vector. This will increase when we add an elements that is outisde of the current vector
-    pub(crate) length: AtomicUsize,
+    /// The slots containing the elements and their versions. Slots that were never initialized have the UNINITIALIZED version
+    pub(crate) slots: Slots<T, u32>,
+    /// The free slots that were not reserved yet and the current length of the vector, which other threads reserve IDs from without locking
+    pub(crate) reservations: Reservations,
+    /// The number of uninitialized slots inside the vector. These were reserved by other threads, but their elements did not arrive yet
+    pub(crate) uninitialized: usize,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
 }
 
 impl<T> Default for ShareableOrderedVec<T> {
     fn default() -> Self {
         Self {
-            vec: Vec::new(),
-            missing: Vec::new(),
-            counter: AtomicUsize::new(0),
-            length: AtomicUsize::new(0),
+            slots: Slots::default(),
+            reservations: Reservations::new(),
+            uninitialized: 0,
+            codec: IdCodec::default(),
         }
     }
 }
@@ -38,233 +207,454 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ShareableOrderedVec")
-            .field("vec", &self.vec)
-            .field("missing", &self.missing)
+            .field("vec", &self.slots.vec)
+            .field("missing", &self.slots.missing)
             .finish()
     }
 }
 
 impl<T> ShareableOrderedVec<T> {
+    /// Create a new shareable ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Default::default()
+        }
+    }
+    /// Create a new shareable ordered vector that can store some elements without re-allocating
+    pub fn with_capacity(n: usize) -> Self {
+        let mut vec = Self::default();
+        vec.reserve(n);
+        vec
+    }
+    /// Create a new shareable ordered vector that grows its slots using a specific growth policy
+    pub fn with_growth_policy(policy: impl GrowthPolicy + 'static) -> Self {
+        let mut vec = Self::default();
+        vec.slots.growth = Some(Arc::new(policy));
+        vec
+    }
+    /// Reserve space for some more elements, on top of the ones that were already handed out by get_next_id_increment
+    /// This should be called before the multi-threaded phase, so inserting at the reserved indices never re-allocates
+    pub fn reserve(&mut self, additional_capacity: usize) {
+        let pending = self
+            .reservations
+            .length()
+            .saturating_sub(self.slots.vec.len());
+        self.slots.vec.reserve(pending + additional_capacity);
+    }
+    /// Get the number of slots that we can store without re-allocating
+    pub fn capacity(&self) -> usize {
+        self.slots.vec.capacity()
+    }
+    /// Get the codec used to pack the IDs of this shareable ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    // Decode an ID that we will insert with, making sure that its index can be stored inside the free list
+    fn decode_insertable(&self, id: Id) -> Result<(usize, u32), InvalidId> {
+        match self.codec.checked_decode(id) {
+            Some((idx, version)) if idx < RESERVED as usize => Ok((idx, version)),
+            _ => Err(InvalidId(id)),
+        }
+    }
     /// Add an element to the ordered vector, but at a specific index (we get that through the ID)
-    /// This will return the last element that was at that index, if possible
-    pub fn insert(&mut self, id: u64, elem: T) -> Option<T> {
+    /// This will return the last element that was at that index, if possible. Null, foreign or out of range IDs get rejected without resizing
+    pub fn insert(&mut self, id: Id, elem: T) -> Result<Option<T>, InvalidId> {
         // Check the length first
-        let pair = from_id(id);
-        let idx = pair.index as usize;
-        self.counter.store(0, Relaxed);
-        if idx >= self.vec.len() {
+        let (idx, version) = self.decode_insertable(id)?;
+        if idx >= self.slots.vec.len() {
             // We must resize and add
-            self.vec.resize_with(idx, || {
+            self.uninitialized += idx - self.slots.vec.len();
+            self.slots.grow(idx + 1 - self.slots.vec.len());
+            self.slots.vec.resize_with(idx, || {
                 // We want to fill the gap with just empty values
-                (None, None)
+                (None, UNINITIALIZED)
             });
             // Actually insert the elements
-            self.vec.push((Some(elem), Some(pair.version)));
-            self.length.fetch_max(self.vec.len(), Relaxed);
-            None
+            self.slots.vec.push((Some(elem), version));
+            self.reservations.cover(self.slots.vec.len());
+            debug_validate("insert", &self.slots.missing, || self.validate());
+            Ok(None)
         } else {
             // Simple overwrite
             // Replace
-            let (old_val, old_version) = self.vec.get_mut(idx).unwrap();
-            // If the value was uninitialized, we must initialize it
-            if old_version.is_none() {
-                *old_version = Some(0);
-                std::mem::replace(old_val, Some(elem))
-            } else {
-                *old_version.as_mut().unwrap() += 1;
-                let missing_idx = self.missing.iter().position(|x| *x == idx);
+            let (old_val, old_version) = self.slots.vec.get_mut(idx).unwrap();
+            // If the value was uninitialized, we must initialize it, otherwise we bump its version
+            let initialized = old_version.initialized();
+            *old_version = old_version.bumped() & self.codec.version_mask();
+            let old = old_val.replace(elem);
+            if initialized {
+                self.slots.reuses += 1;
+                let missing_idx = self.slots.missing.iter().position(|x| *x == idx);
                 if let Some(missing_idx) = missing_idx {
-                    self.missing.remove(missing_idx);
+                    self.slots.missing.remove(missing_idx);
+                }
+                // The slot might have been filled without being reserved first
+                if old.is_none() {
+                    self.reservations.unlink(idx);
                 }
-                std::mem::replace(old_val, Some(elem))
+            } else {
+                self.uninitialized -= 1;
             }
+            debug_validate("insert", &self.slots.missing, || self.validate());
+            Ok(old)
         }
     }
-    /// Get the ID of the next element that we will add. If we call this twice, without inserting any elements, it will not change
-    pub fn get_next_id(&self) -> u64 {
-        // Normal push
-        let index = self.missing.last().cloned().unwrap_or(self.vec.len());
-        let (_, version) = self.vec.get(index).unwrap();
-        to_id(IndexPair::new(index, version.unwrap_or(0)))
+    /// Add an element to the ordered vector, but only if its ID could have been handed out by get_next_id_increment
+    /// This catches IDs that were fabricated, since it never overwrites a live element, never fills a free slot that nobody reserved, and never resizes past the reserved indices
+    pub fn insert_strict(&mut self, id: Id, elem: T) -> Result<Option<T>, Unreserved> {
+        let (idx, version) = self.codec.checked_decode(id).ok_or(Unreserved(id))?;
+        let (occupied, expected) = self
+            .slots
+            .vec
+            .get(idx)
+            .map_or((false, 0), |(val, version)| {
+                (val.is_some(), version.bumped())
+            });
+        // Slots past the end are pending reservations, and vacant slots must have been popped from the free list
+        let reserved = idx < self.reservations.length() && self.reservations.is_reserved(idx);
+        if occupied || !reserved || version != expected & self.codec.version_mask() {
+            return Err(Unreserved(id));
+        }
+        self.insert(id, elem).map_err(|_| Unreserved(id))
     }
-    /// Check the next index where we can add an element, but also increment the counter, so it won't be the same index
-    /// This assumes that we wille eventually insert an element at said index
-    pub fn get_next_id_increment(&self) -> u64 {
-        // Try to get an empty cell, if we couldn't just use the length as the index
-        let ctr = self.counter.fetch_add(1, Relaxed);
-        // Calculate the index from the back to front
-        let missing_idx = self.missing.len().checked_sub(ctr + 1);
-        let index = if let Some(missing_idx) = missing_idx {
-            if let Some(idx) = self.missing.get(missing_idx) {
-                *idx
+    /// Put an element at exactly the index and version of an ID, and return the last element that was at that index
+    /// Unlike insert(), this never bumps the version, so the element can be fetched using that same ID afterwards. Null, foreign or out of range IDs get rejected
+    pub fn place(&mut self, id: Id, elem: T) -> Result<Option<T>, InvalidId> {
+        let (idx, version) = self.decode_insertable(id)?;
+        let len = self.slots.vec.len();
+        let reserved = self.reservations.length();
+        if self
+            .slots
+            .vec
+            .get(idx)
+            .is_some_and(|(_, version)| !version.initialized())
+        {
+            self.uninitialized -= 1;
+        }
+        // The gap gets filled with vacant slots, so they can be re-used later on
+        let old = self.slots.insert_at(idx, version, elem);
+        for gap in len..idx {
+            if gap < reserved {
+                // Other threads reserved this slot already, so it must wait for its element instead of getting freed
+                self.slots.vec[gap].1 = UNINITIALIZED;
+                self.slots.missing.retain(|x| *x != gap);
+                self.uninitialized += 1;
             } else {
-                self.length.fetch_add(1, Relaxed)
+                self.reservations.push(gap);
             }
-        } else {
-            self.length.fetch_add(1, Relaxed)
-        };
-        let version = if let Some((_, index)) = self.vec.get(index) {
-            index.unwrap_or(0) + 1
-        } else {
-            0
-        };
-        to_id(IndexPair::new(index, version))
+        }
+        if old.is_none() {
+            self.reservations.unlink(idx);
+        }
+        self.reservations.cover(self.slots.vec.len());
+        Ok(old)
+    }
+    /// Get the ID of the next element that we will add. If we call this twice, without inserting any elements, it will not change
+    pub fn get_next_id(&self) -> Id {
+        self.next_id_at(self.reservations.peek())
+    }
+    /// Check the next index where we can add an element, but also reserve it, so it won't be the same index
+    /// This assumes that we wille eventually insert an element at said index
+    pub fn get_next_id_increment(&self) -> Id {
+        // Try to pop an empty cell, if we couldn't just use the length as the index
+        self.next_id_at(self.reservations.reserve())
+    }
+    // Get the ID that an element will have once it gets inserted at a specific index
+    fn next_id_at(&self, index: usize) -> Id {
+        // Uninitialized slots will start at version 0, just like brand new ones
+        let version = self
+            .slots
+            .vec
+            .get(index)
+            .map_or(0, |(_, version)| version.bumped());
+        self.codec
+            .encode(index, version & self.codec.version_mask())
     }
     /// Remove an element that is contained in the shareable vec
-    pub fn remove(&mut self, id: u64) -> Option<T> {
-        let pair = from_id(id);
-        self.missing.push(pair.index as usize);
-        let (elem, version) = self.vec.get_mut(pair.index as usize)?;
-        // Only remove if the version is the same as well
-        if pair.version != *(version.as_ref()?) {
-            return None;
-        }
-        std::mem::take(elem)
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)?;
+        self.remove_index(index)
     }
     /// Remove an element that is contained in the vec. This does not check if the element's version matches up with the ID!
     pub fn remove_index(&mut self, index: usize) -> Option<T> {
-        self.missing.push(index);
-        let (elem, _) = self.vec.get_mut(index as usize)?;
-        std::mem::take(elem)
+        let elem = self.slots.remove_index(index)?;
+        self.reservations.push(index);
+        Some(elem)
     }
     /// Get a reference to an element in the ordered vector
-    pub fn get(&self, id: u64) -> Option<&T> {
-        let pair = from_id(id);
-        // First of all check if we *might* contain the cell
-        return if (pair.index as usize) < self.vec.len() {
-            // We contain the cell, but it might be null
-            let (cell, version) = self.vec.get(pair.index as usize)?;
-            // Check if the versions are the same
-            if pair.version == *(version.as_ref()?) {
-                cell.as_ref()
-            } else {
-                None
-            }
-        } else {
-            // We do not contain the cell at all
-            None
-        };
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
     }
     /// Get a mutable reference to an element in the ordered vector
-    pub fn get_mut(&mut self, id: u64) -> Option<&mut T> {
-        let pair = from_id(id);
-        // First of all check if we *might* contain the cell
-        return if (pair.index as usize) < self.vec.len() {
-            // We contain the cell, but it might be null
-            let (cell, version) = self.vec.get_mut(pair.index as usize)?;
-            // Check if the versions are the same
-            if pair.version == *(version.as_ref()?) {
-                cell.as_mut()
-            } else {
-                None
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get_mut(index, version)
+    }
+    /// Get mutable references to several elements at once, like when moving items between two inventories
+    /// This returns None if one of the IDs is invalid, or if two of them point to the same slot
+    pub fn get_disjoint_mut<const N: usize>(&mut self, ids: [Id; N]) -> Option<[&mut T; N]> {
+        let mut keys = [(0, 0); N];
+        for (key, id) in keys.iter_mut().zip(ids) {
+            *key = self.codec.checked_decode(id)?;
+        }
+        self.slots.get_disjoint_mut(keys)
+    }
+    /// Only keep the elements that return true, and remove the rest
+    /// The pending reservations stay valid, since only the indices of the removed elements get added to the free list
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(Id, &T) -> bool,
+    {
+        let codec = self.codec;
+        let removed = self
+            .slots
+            .filtered(|index, version, val| !keep(codec.encode(index, version), val));
+        for (index, _) in removed {
+            self.slots.remove_index(index);
+            self.reservations.push(index);
+        }
+    }
+    /// Check the structural invariants of the shareable ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()?;
+        // Every slot that can still be reserved must be empty, and must only be reserved once
+        let mut reservable = vec![false; self.slots.vec.len()];
+        for index in self.reservations.iter() {
+            match self.slots.vec.get(index) {
+                None => {
+                    return Err(InvariantError::FreeSlotOutOfBounds {
+                        index,
+                        len: self.slots.vec.len(),
+                    })
+                }
+                Some((Some(_), _)) => return Err(InvariantError::FreeSlotOccupied { index }),
+                _ if reservable[index] => return Err(InvariantError::DuplicateFreeSlot { index }),
+                _ => reservable[index] = true,
             }
-        } else {
-            // We do not contain the cell at all
-            None
-        };
+        }
+        let uninitialized = self
+            .slots
+            .vec
+            .iter()
+            .filter(|(_, version)| !version.initialized())
+            .count();
+        check_len("uninitialized slots", uninitialized, self.uninitialized)?;
+        // The length includes the slots that were reserved but not inserted yet, so it can never fall behind
+        let length = self.reservations.length();
+        if length < self.slots.vec.len() {
+            return Err(InvariantError::LengthMismatch {
+                what: "length counter",
+                expected: self.slots.vec.len(),
+                found: length,
+            });
+        }
+        Ok(())
     }
-    /// Get the number of valid elements in the ordered vector
+    /// Get the memory and fragmentation statistics of the shareable ordered vector
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            live: self.count(),
+            ..self.slots.metrics()
+        }
+    }
+    /// Get the number of valid elements in the ordered vector. This never includes the slots that were reserved, but that were not inserted into yet
     pub fn count(&self) -> usize {
-        self.vec.len() - self.missing.len()
+        self.slots.count() - self.uninitialized
     }
-    /// Get the number of invalid elements in the ordered vector
+    /// Get the number of invalid elements in the ordered vector. This includes the free slots that were reserved, but not the slots that were reserved past them
     pub fn count_invalid(&self) -> usize {
-        self.missing.len()
+        self.slots.count_invalid()
+    }
+    /// Get the number of slots that were reserved using get_next_id_increment, but that were not inserted into yet
+    pub fn count_reserved(&self) -> usize {
+        let reused = self
+            .slots
+            .missing
+            .iter()
+            .filter(|index| self.reservations.is_reserved(**index))
+            .count();
+        reused + self.uninitialized + (self.reservations.length() - self.slots.vec.len())
     }
     /// Clear the whole shareable ordered vector
     pub fn clear(&mut self) -> Vec<Option<T>> {
-        // Simple clear
-        let rep = std::mem::take(&mut self.vec);
-        self.missing.clear();
-        rep.into_iter().map(|(val, _)| val).collect::<Vec<_>>()
+        self.reservations = Reservations::new();
+        self.uninitialized = 0;
+        self.slots.clear()
     }
 }
 
 /// Iter magic
 impl<T> ShareableOrderedVec<T> {
-    /// Convert this into an iterator
-    pub fn into_iter(self) -> impl Iterator<Item = (u64, T)> {
-        self.vec
-            .into_iter()
-            .enumerate()
-            .filter_map(|(index, (val, version))| {
-                val.map(|val| (to_id(IndexPair::new(index, version.unwrap())), val))
-            })
-    }
     /// Get an iterator over the valid elements
     pub fn iter_elements(&self) -> impl Iterator<Item = &T> {
-        self.vec.iter().filter_map(|(val, _)| val.as_ref())
+        self.slots.iter_elements()
     }
     /// Get a mutable iterator over the valid elements
     pub fn iter_elements_mut(&mut self) -> impl Iterator<Item = &mut T> {
-        self.vec.iter_mut().filter_map(|(val, _)| val.as_mut())
+        self.slots.iter_elements_mut()
     }
     /// Get an iterator over the valid elements, but with the ID of each element
-    pub fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
-        self.vec
-            .iter()
-            .enumerate()
-            .filter_map(|(index, (val, version))| {
-                val.as_ref().map(|val| {
-                    (
-                        to_id(IndexPair::new(index, *(version.as_ref().unwrap()))),
-                        val,
-                    )
-                })
-            })
+    pub fn iter(&self) -> ShareableIter<'_, T> {
+        ShareableIter {
+            slots: self.slots.vec.iter().enumerate(),
+            codec: self.codec,
+        }
     }
     /// Get a mutable iterator over the valid elements, but with the ID of each element
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u64, &mut T)> {
-        self.vec
-            .iter_mut()
-            .enumerate()
-            .filter_map(|(index, (val, version))| {
-                val.as_mut().map(|val| {
-                    (
-                        to_id(IndexPair::new(index, *(version.as_ref().unwrap()))),
-                        val,
-                    )
-                })
-            })
+    pub fn iter_mut(&mut self) -> ShareableIterMut<'_, T> {
+        ShareableIterMut {
+            slots: self.slots.vec.iter_mut().enumerate(),
+            codec: self.codec,
+        }
     }
     /// Get an iterator over the indices of the null elements
     pub fn iter_invalid(&self) -> impl Iterator<Item = &usize> {
-        self.missing.iter()
+        self.slots.missing.iter()
+    }
+    /// Get an iterator over the IDs that were reserved using get_next_id_increment, but whose elements were not inserted yet, sorted by index
+    /// This lets the owner thread show placeholders for the elements that did not arrive yet. Unlike iter(), this skips the occupied and the vacant slots
+    pub fn iter_reserved(&self) -> impl Iterator<Item = Id> + '_ {
+        let reserved = self
+            .slots
+            .vec
+            .iter()
+            .enumerate()
+            .filter(|(index, (val, version))| {
+                val.is_none() && (!version.initialized() || self.reservations.is_reserved(*index))
+            })
+            .map(|(index, _)| index);
+        reserved
+            .chain(self.slots.vec.len()..self.reservations.length())
+            .map(|index| self.next_id_at(index))
     }
     /// Drain the elements that only return true. This will return just an Iterator of the index and value of the drained elements
-    pub fn my_drain<F>(&mut self, mut filter: F) -> impl Iterator<Item = (u64, T)> + '_
+    /// The elements are removed lazily, so dropping the iterator early leaves the remaining elements untouched
+    pub fn my_drain<'a, F>(&'a mut self, mut filter: F) -> impl Iterator<Item = (Id, T)> + 'a
     where
-        F: FnMut(u64, &T) -> bool,
+        F: FnMut(Id, &T) -> bool + 'a,
     {
-        // Keep track of the IDs that we must remove
-        let mut removed_ids: Vec<u64> = Vec::new();
-        for (index, (val, version)) in self.vec.iter_mut().enumerate() {
-            if let Some(val) = val {
-                // If it validates the filter, we must remove it
-                let id = to_id(IndexPair::new(index, *(version.as_ref().unwrap())));
-                if filter(id, val) {
-                    // We must remove this value
-                    removed_ids.push(id);
-                }
+        let codec = self.codec;
+        let reservations = &mut self.reservations;
+        self.slots
+            .drain_filter(move |index, version, val| filter(codec.encode(index, version), val))
+            .map(move |(index, version, val)| {
+                reservations.push(index);
+                (codec.encode(index, version), val)
+            })
+    }
+}
+
+/// A consuming iterator over the valid elements of a shareable ordered vector, with the ID of each element
+pub struct ShareableIntoIter<T> {
+    /// The slots that we have not visited yet, with their index
+    slots: std::iter::Enumerate<std::vec::IntoIter<(Option<T>, u32)>>,
+    /// The codec used to pack the IDs
+    codec: IdCodec,
+}
+
+impl<T> Iterator for ShareableIntoIter<T> {
+    type Item = (Id, T);
+    fn next(&mut self) -> Option<Self::Item> {
+        let codec = self.codec;
+        self.slots
+            .find_map(|(index, (val, version))| val.map(|val| (codec.encode(index, version), val)))
+    }
+}
+
+/// An iterator over the valid elements of a shareable ordered vector, with the ID of each element
+pub struct ShareableIter<'a, T> {
+    /// The slots that we have not visited yet, with their index
+    slots: std::iter::Enumerate<std::slice::Iter<'a, (Option<T>, u32)>>,
+    /// The codec used to pack the IDs
+    codec: IdCodec,
+}
+
+impl<'a, T> Iterator for ShareableIter<'a, T> {
+    type Item = (Id, &'a T);
+    fn next(&mut self) -> Option<Self::Item> {
+        let codec = self.codec;
+        self.slots.find_map(|(index, (val, version))| {
+            val.as_ref().map(|val| (codec.encode(index, *version), val))
+        })
+    }
+}
+
+/// A mutable iterator over the valid elements of a shareable ordered vector, with the ID of each element
+pub struct ShareableIterMut<'a, T> {
+    /// The slots that we have not visited yet, with their index
+    slots: std::iter::Enumerate<std::slice::IterMut<'a, (Option<T>, u32)>>,
+    /// The codec used to pack the IDs
+    codec: IdCodec,
+}
+
+impl<'a, T> Iterator for ShareableIterMut<'a, T> {
+    type Item = (Id, &'a mut T);
+    fn next(&mut self) -> Option<Self::Item> {
+        let codec = self.codec;
+        self.slots.find_map(|(index, (val, version))| {
+            val.as_mut().map(|val| (codec.encode(index, *version), val))
+        })
+    }
+}
+
+/// Traits
+impl<'a, T> IntoIterator for &'a ShareableOrderedVec<T> {
+    type Item = (Id, &'a T);
+    type IntoIter = ShareableIter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ShareableOrderedVec<T> {
+    type Item = (Id, &'a mut T);
+    type IntoIter = ShareableIterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> IntoIterator for ShareableOrderedVec<T> {
+    type Item = (Id, T);
+    type IntoIter = ShareableIntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        ShareableIntoIter {
+            slots: self.slots.vec.into_iter().enumerate(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Extend<(Id, T)> for ShareableOrderedVec<T> {
+    fn extend<I: IntoIterator<Item = (Id, T)>>(&mut self, iter: I) {
+        for (id, elem) in iter {
+            if let Err(err) = self.place(id, elem) {
+                panic!("{}", err);
             }
         }
-        // Now we can actually remove the objects
-        removed_ids
-            .into_iter()
-            .map(|id| (id, self.remove(id).unwrap()))
     }
 }
 
-/// Traits
-impl<T> Index<u64> for ShareableOrderedVec<T> {
+impl<T> FromIterator<(Id, T)> for ShareableOrderedVec<T> {
+    fn from_iter<I: IntoIterator<Item = (Id, T)>>(iter: I) -> Self {
+        let mut vec = Self::default();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T> Index<Id> for ShareableOrderedVec<T> {
     type Output = T;
-    fn index(&self, index: u64) -> &Self::Output {
+    fn index(&self, index: Id) -> &Self::Output {
         self.get(index).unwrap()
     }
 }
 
-impl<T> IndexMut<u64> for ShareableOrderedVec<T> {
-    fn index_mut(&mut self, index: u64) -> &mut Self::Output {
+impl<T> IndexMut<Id> for ShareableOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
         self.get_mut(index).unwrap()
     }
 }
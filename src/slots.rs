@@ -0,0 +1,546 @@
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+
+use crate::{
+    invariant::{check_free_list, debug_validate, InvariantError},
+    metrics::{largest_free_run, Metrics},
+    utils::{GrowthPolicy, ReusePolicy},
+};
+
+/// The version of the slots that were created to fill a gap, but that never held an element
+/// Bumping it wraps around to the first version, so these slots behave just like brand new ones
+pub(crate) const UNINITIALIZED: u32 = u32::MAX;
+
+/// The version that gets stored alongside each slot
+pub(crate) trait SlotVersion: Copy + Debug {
+    /// The version of the first element that gets put inside a brand new slot
+    fn first() -> Self;
+    /// The version that the next element put inside this slot will have
+    fn bumped(self) -> Self;
+    /// Check if this version matches up with the version stored inside an ID
+    fn matches(self, version: u32) -> bool;
+    /// Get the version that we must store inside an ID
+    fn raw(self) -> u32;
+    /// Check if the slot was ever initialized. Uninitialized slots don't need to be in the free list
+    fn initialized(self) -> bool {
+        true
+    }
+}
+
+// Unversioned slots always match
+impl SlotVersion for () {
+    fn first() -> Self {}
+    fn bumped(self) -> Self {}
+    fn matches(self, _version: u32) -> bool {
+        true
+    }
+    fn raw(self) -> u32 {
+        0
+    }
+}
+
+// Versioned slots
+impl SlotVersion for u32 {
+    fn first() -> Self {
+        0
+    }
+    fn bumped(self) -> Self {
+        self.wrapping_add(1)
+    }
+    fn matches(self, version: u32) -> bool {
+        self == version
+    }
+    fn raw(self) -> u32 {
+        self
+    }
+    fn initialized(self) -> bool {
+        self != UNINITIALIZED
+    }
+}
+
+// Versioned slots with a compact version
+impl SlotVersion for u8 {
+    fn first() -> Self {
+        0
+    }
+    fn bumped(self) -> Self {
+        self.wrapping_add(1)
+    }
+    fn matches(self, version: u32) -> bool {
+        self as u32 == version
+    }
+    fn raw(self) -> u32 {
+        self as u32
+    }
+}
+
+/// The storage that the slots live in. A plain Vec is the default, but the ordered vectors that can't keep their slots inside a single Vec implement this too, so they can share the engine
+pub(crate) trait SlotStorage<T, V> {
+    /// Get the total number of slots
+    fn len(&self) -> usize;
+    /// Get a slot
+    fn slot(&self, index: usize) -> Option<&(Option<T>, V)>;
+    /// Iterate over all the slots in order
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a (Option<T>, V)>
+    where
+        T: 'a,
+        V: 'a;
+    /// Get the number of bytes allocated for the slots
+    fn bytes_allocated(&self) -> usize;
+}
+
+/// The storage that the slots live in, when it can be modified
+pub(crate) trait SlotStorageMut<T, V>: SlotStorage<T, V> {
+    /// Get a slot mutably
+    fn slot_mut(&mut self, index: usize) -> Option<&mut (Option<T>, V)>;
+    /// Iterate mutably over all the slots in order
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut (Option<T>, V)>
+    where
+        T: 'a,
+        V: 'a;
+    /// Add a brand new slot at the end, or give it back if there is no space left
+    fn push(&mut self, slot: (Option<T>, V)) -> Result<(), (Option<T>, V)>;
+    /// Remove all the slots
+    fn clear(&mut self);
+    /// Make sure that we can add `additional` slots without re-allocating. Storages that never move their slots ignore this
+    fn grow(&mut self, _additional: usize, _policy: Option<&dyn GrowthPolicy>) {}
+}
+
+impl<T, V> SlotStorage<T, V> for Vec<(Option<T>, V)> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn slot(&self, index: usize) -> Option<&(Option<T>, V)> {
+        self.get(index)
+    }
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a (Option<T>, V)>
+    where
+        T: 'a,
+        V: 'a,
+    {
+        self.as_slice().iter()
+    }
+    fn bytes_allocated(&self) -> usize {
+        self.capacity() * std::mem::size_of::<(Option<T>, V)>()
+    }
+}
+
+impl<T, V> SlotStorageMut<T, V> for Vec<(Option<T>, V)> {
+    fn slot_mut(&mut self, index: usize) -> Option<&mut (Option<T>, V)> {
+        self.get_mut(index)
+    }
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut (Option<T>, V)>
+    where
+        T: 'a,
+        V: 'a,
+    {
+        self.as_mut_slice().iter_mut()
+    }
+    fn push(&mut self, slot: (Option<T>, V)) -> Result<(), (Option<T>, V)> {
+        Vec::push(self, slot);
+        Ok(())
+    }
+    fn clear(&mut self) {
+        *self = Vec::new();
+    }
+    fn grow(&mut self, additional: usize, policy: Option<&dyn GrowthPolicy>) {
+        let needed = Vec::len(self) + additional;
+        if needed <= self.capacity() {
+            return;
+        }
+        if let Some(policy) = policy {
+            let capacity = policy.next_capacity(self.capacity(), needed).max(needed);
+            self.reserve_exact(capacity - Vec::len(self));
+        }
+    }
+}
+
+/// The list of the indices that contain a null element. A plain Vec is the default
+pub(crate) trait FreeSlots {
+    /// Get the free indices, in the order they were freed
+    fn as_slice(&self) -> &[usize];
+    /// Add a free index at the end
+    fn push(&mut self, index: usize);
+    /// Take out the free index at a specific position, keeping the order of the others
+    fn remove(&mut self, position: usize) -> usize;
+    /// Take out the free index at a specific position, moving the last one into its place
+    fn swap_remove(&mut self, position: usize) -> usize;
+    /// Only keep the free indices that return true
+    fn retain(&mut self, keep: impl FnMut(&usize) -> bool);
+    /// Remove all the free indices
+    fn clear(&mut self);
+    /// Get the number of bytes allocated for the free list
+    fn bytes_allocated(&self) -> usize;
+}
+
+impl FreeSlots for Vec<usize> {
+    fn as_slice(&self) -> &[usize] {
+        self
+    }
+    fn push(&mut self, index: usize) {
+        Vec::push(self, index)
+    }
+    fn remove(&mut self, position: usize) -> usize {
+        Vec::remove(self, position)
+    }
+    fn swap_remove(&mut self, position: usize) -> usize {
+        Vec::swap_remove(self, position)
+    }
+    fn retain(&mut self, keep: impl FnMut(&usize) -> bool) {
+        Vec::retain(self, keep)
+    }
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+    fn bytes_allocated(&self) -> usize {
+        self.capacity() * std::mem::size_of::<usize>()
+    }
+}
+
+/// The slot management engine that is shared by all the ordered vectors
+/// This keeps track of the occupancy, the versions, and the free list of the slots
+pub(crate) struct Slots<T, V, S = Vec<(Option<T>, V)>, M = Vec<usize>> {
+    /// A list of the current elements in the list, with their version
+    pub(crate) vec: S,
+    /// A list of the indices that contain a null element, so whenever we add a new element, we will add it there
+    pub(crate) missing: M,
+    /// Which free slot gets re-used first
+    pub(crate) policy: ReusePolicy,
+    /// The number of times we re-used a free slot
+    pub(crate) reuses: u64,
+    /// How much capacity we get when we must grow. None lets the storage decide
+    pub(crate) growth: Option<Arc<dyn GrowthPolicy>>,
+    /// The slots own their elements, even when the storage hides them
+    pub(crate) marker: PhantomData<(T, V)>,
+}
+
+impl<T, V, S, M> Clone for Slots<T, V, S, M>
+where
+    S: Clone,
+    M: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            vec: self.vec.clone(),
+            missing: self.missing.clone(),
+            policy: self.policy,
+            reuses: self.reuses,
+            growth: self.growth.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, V, S: Default, M: Default> Default for Slots<T, V, S, M> {
+    fn default() -> Self {
+        Self {
+            vec: S::default(),
+            missing: M::default(),
+            policy: ReusePolicy::default(),
+            reuses: 0,
+            growth: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, V: SlotVersion> Slots<T, V> {
+    /// Create the slots using already existing elements
+    pub(crate) fn from_valids(vals: Vec<T>) -> Self {
+        Self {
+            vec: vals
+                .into_iter()
+                .map(|x| (Some(x), V::first()))
+                .collect::<Vec<_>>(),
+            ..Self::default()
+        }
+    }
+    /// Get mutable references to several elements at once, but only if all the versions match up and all the indices are different
+    pub(crate) fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        keys: [(usize, u32); N],
+    ) -> Option<[&mut T; N]> {
+        let slots = self
+            .vec
+            .get_disjoint_mut(keys.map(|(index, _)| index))
+            .ok()?;
+        // Check all the versions before handing out any reference
+        let valid = slots
+            .iter()
+            .zip(keys.iter())
+            .all(|(slot, (_, version))| slot.0.is_some() && slot.1.matches(*version));
+        valid.then(|| slots.map(|(cell, _)| cell.as_mut().unwrap()))
+    }
+    /// Convert the slots into an iterator over the valid elements, with their index and version
+    pub(crate) fn into_iter(self) -> impl Iterator<Item = (usize, u32, T)> {
+        self.vec
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, (val, version))| val.map(|val| (index, version.raw(), val)))
+    }
+}
+
+impl<T, V, S: Default, M: Default> Slots<T, V, S, M> {
+    /// Create empty slots that re-use their free slots using a specific policy
+    pub(crate) fn with_policy(policy: ReusePolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+}
+
+impl<T, V: SlotVersion, S: SlotStorage<T, V>, M: FreeSlots> Slots<T, V, S, M> {
+    // Get the position (inside the missing list) of the free slot that we must re-use next
+    fn next_missing(&self) -> Option<usize> {
+        let missing = self.missing.as_slice();
+        if missing.is_empty() {
+            return None;
+        }
+        match self.policy {
+            ReusePolicy::Lifo => Some(missing.len() - 1),
+            ReusePolicy::Fifo => Some(0),
+            ReusePolicy::LowestIndex => missing
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, index)| **index)
+                .map(|(position, _)| position),
+        }
+    }
+    /// Get the index and version of the slot that the next element will be added to
+    pub(crate) fn next_slot(&self) -> (usize, V) {
+        match self
+            .next_missing()
+            .map(|position| self.missing.as_slice()[position])
+        {
+            // Shove
+            Some(index) => (index, self.vec.slot(index).unwrap().1.bumped()),
+            // Normal push
+            None => (self.vec.len(), V::first()),
+        }
+    }
+    /// Get a reference to an element, but only if the versions match up
+    pub(crate) fn get(&self, index: usize, version: u32) -> Option<&T> {
+        let (cell, cell_version) = self.vec.slot(index)?;
+        if cell_version.matches(version) {
+            cell.as_ref()
+        } else {
+            None
+        }
+    }
+    /// Get the number of valid elements
+    pub(crate) fn count(&self) -> usize {
+        self.vec.len() - self.missing.as_slice().len()
+    }
+    /// Get the number of invalid elements
+    pub(crate) fn count_invalid(&self) -> usize {
+        self.missing.as_slice().len()
+    }
+    /// Get the memory and fragmentation statistics of the slots
+    pub(crate) fn metrics(&self) -> Metrics {
+        Metrics {
+            bytes_allocated: self.vec.bytes_allocated() + self.missing.bytes_allocated(),
+            live: self.count(),
+            holes: self.count_invalid(),
+            largest_free_run: largest_free_run(self.vec.iter().map(|(val, _)| val.is_some())),
+            reuses: self.reuses,
+        }
+    }
+    /// Check that the free list matches up with the empty slots
+    pub(crate) fn validate(&self) -> Result<(), InvariantError> {
+        check_free_list(
+            self.vec
+                .iter()
+                .map(|(val, version)| version.initialized().then_some(val.is_some())),
+            self.missing.as_slice().iter().copied(),
+        )
+    }
+}
+
+impl<T, V: SlotVersion, S: SlotStorageMut<T, V>, M: FreeSlots> Slots<T, V, S, M> {
+    /// Make sure that we can add `additional` slots without re-allocating, using the growth policy if we have one
+    pub(crate) fn grow(&mut self, additional: usize) {
+        self.vec.grow(additional, self.growth.as_deref());
+    }
+    /// Add an element, and return the index and version of the slot it was added to
+    pub(crate) fn push_shove(&mut self, elem: T) -> (usize, V) {
+        match self.try_push_shove(elem) {
+            Ok(slot) => slot,
+            Err(_) => panic!("The slots are full"),
+        }
+    }
+    /// Add an element, and return the index and version of the slot it was added to. If the storage is full, the element gets returned back
+    pub(crate) fn try_push_shove(&mut self, elem: T) -> Result<(usize, V), T> {
+        let slot = if let Some(position) = self.next_missing() {
+            // FIFO must keep the order of the other free slots
+            let index = match self.policy {
+                ReusePolicy::Fifo => self.missing.remove(position),
+                _ => self.missing.swap_remove(position),
+            };
+            // If we have some null elements, we can validate the given element there
+            let (old_val, old_version) = self.vec.slot_mut(index).unwrap();
+            *old_val = Some(elem);
+            *old_version = old_version.bumped();
+            let version = *old_version;
+            self.reuses += 1;
+            (index, version)
+        } else {
+            // Add the element normally
+            let version = V::first();
+            self.grow(1);
+            if let Err((elem, _)) = self.vec.push((Some(elem), version)) {
+                return Err(elem.unwrap());
+            }
+            (self.vec.len() - 1, version)
+        };
+        debug_validate("push_shove", self.missing.as_slice(), || self.validate());
+        Ok(slot)
+    }
+    /// Get a mutable reference to an element, but only if the versions match up
+    pub(crate) fn get_mut(&mut self, index: usize, version: u32) -> Option<&mut T> {
+        let (cell, cell_version) = self.vec.slot_mut(index)?;
+        if cell_version.matches(version) {
+            cell.as_mut()
+        } else {
+            None
+        }
+    }
+    /// Remove an element, but only if the versions match up
+    pub(crate) fn remove(&mut self, index: usize, version: u32) -> Option<T> {
+        let (_, cell_version) = self.vec.slot(index)?;
+        if !cell_version.matches(version) {
+            return None;
+        }
+        self.remove_index(index)
+    }
+    /// Remove an element without checking its version
+    pub(crate) fn remove_index(&mut self, index: usize) -> Option<T> {
+        // Don't touch the slot if it is already empty, since that might copy it
+        self.vec.slot(index)?.0.as_ref()?;
+        let elem = self.vec.slot_mut(index)?.0.take()?;
+        // Only free the slot once
+        self.missing.push(index);
+        debug_validate("remove", self.missing.as_slice(), || self.validate());
+        Some(elem)
+    }
+    /// Put an element inside a specific slot with a specific version, and return the element that was there
+    pub(crate) fn insert_at(&mut self, index: usize, version: V, elem: T) -> Option<T> {
+        // Fill the gap with vacant slots
+        self.grow((index + 1).saturating_sub(self.vec.len()));
+        while self.vec.len() <= index {
+            self.missing.push(self.vec.len());
+            if self.vec.push((None, V::first())).is_err() {
+                panic!("The slots are full");
+            }
+        }
+        let (old_val, old_version) = self.vec.slot_mut(index).unwrap();
+        *old_version = version;
+        let old = old_val.replace(elem);
+        if old.is_none() {
+            self.missing.retain(|x| *x != index);
+        }
+        debug_validate("insert", self.missing.as_slice(), || self.validate());
+        old
+    }
+    /// Remove all the slots and free indices, dropping the elements
+    pub(crate) fn reset(&mut self) {
+        self.vec.clear();
+        self.missing.clear();
+    }
+    /// Clear all the slots, returning the old elements
+    pub(crate) fn clear(&mut self) -> Vec<Option<T>> {
+        let rep = self
+            .vec
+            .iter_mut()
+            .map(|(val, _)| val.take())
+            .collect::<Vec<_>>();
+        self.reset();
+        rep
+    }
+}
+
+/// Iter magic
+impl<T, V: SlotVersion, S: SlotStorage<T, V>, M> Slots<T, V, S, M> {
+    /// Get an iterator over the valid elements, with their index and version
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, u32, &T)> {
+        self.vec
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (val, version))| {
+                val.as_ref().map(|val| (index, version.raw(), val))
+            })
+    }
+    /// Get an iterator over the valid elements
+    pub(crate) fn iter_elements(&self) -> impl Iterator<Item = &T> {
+        self.vec.iter().filter_map(|(val, _)| val.as_ref())
+    }
+    /// Collect the index and version of every valid element that passes the filter
+    pub(crate) fn filtered(
+        &self,
+        mut filter: impl FnMut(usize, u32, &T) -> bool,
+    ) -> Vec<(usize, u32)> {
+        self.iter()
+            .filter(|(index, version, val)| filter(*index, *version, val))
+            .map(|(index, version, _)| (index, version))
+            .collect()
+    }
+}
+
+impl<T, V: SlotVersion, S: SlotStorageMut<T, V>, M: FreeSlots> Slots<T, V, S, M> {
+    /// Get a mutable iterator over the valid elements, with their index and version
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = (usize, u32, &mut T)> {
+        self.vec
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, (val, version))| {
+                val.as_mut().map(|val| (index, version.raw(), val))
+            })
+    }
+    /// Get a mutable iterator over the valid elements
+    pub(crate) fn iter_elements_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.vec.iter_mut().filter_map(|(val, _)| val.as_mut())
+    }
+    /// Lazily remove the valid elements that pass the filter, returning their index, version and value
+    pub(crate) fn drain_filter<F>(&mut self, filter: F) -> DrainFilter<'_, T, V, S, M, F>
+    where
+        F: FnMut(usize, u32, &T) -> bool,
+    {
+        DrainFilter {
+            slots: self,
+            index: 0,
+            filter,
+        }
+    }
+}
+
+/// A lazy iterator that removes the valid elements that pass a filter as it advances
+/// Dropping it early leaves the elements that were not visited yet untouched
+pub(crate) struct DrainFilter<'a, T, V, S, M, F> {
+    /// The slots that we are draining
+    slots: &'a mut Slots<T, V, S, M>,
+    /// The index of the next slot that we must check
+    index: usize,
+    /// The filter that tells us which elements to remove
+    filter: F,
+}
+
+impl<T, V: SlotVersion, S: SlotStorageMut<T, V>, M: FreeSlots, F> Iterator
+    for DrainFilter<'_, T, V, S, M, F>
+where
+    F: FnMut(usize, u32, &T) -> bool,
+{
+    type Item = (usize, u32, T);
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((val, version)) = self.slots.vec.slot(self.index) {
+            let index = self.index;
+            self.index += 1;
+            let version = version.raw();
+            if val
+                .as_ref()
+                .is_some_and(|val| (self.filter)(index, version, val))
+            {
+                return Some((index, version, self.slots.remove_index(index).unwrap()));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::InvariantError,
+    metrics::Metrics,
+    slots::Slots,
+    utils::{Id32, ReusePolicy},
+};
+
+/// An ordered vector that hands out compact 32 bit IDs instead of 64 bit ones
+/// This can only contain up to 2^24 slots, and the versions wrap around after 256 re-uses of the same slot
+pub struct SmallOrderedVec<T> {
+    /// The slots containing the elements and their versions
+    pub(crate) slots: Slots<T, u8>,
+}
+
+impl<T> Clone for SmallOrderedVec<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+impl<T> Debug for SmallOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmallOrderedVec")
+            .field("vec", &self.slots.vec)
+            .field("missing", &self.slots.missing)
+            .finish()
+    }
+}
+
+impl<T> Default for SmallOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            slots: Slots::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<T> SmallOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new ordered vector that re-uses its free slots using a specific policy
+    /// FIFO works great here, since the versions of this vector wrap around quickly
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            slots: Slots::with_policy(policy),
+        }
+    }
+    /// Get the policy used to re-use the free slots
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        self.slots.policy
+    }
+    /// Add an element to the ordered vector
+    ///
+    /// # Panics
+    /// This panics if we run out of addressable slots. Use try_push_shove() to get the element back instead
+    pub fn push_shove(&mut self, elem: T) -> Id32 {
+        match self.try_push_shove(elem) {
+            Ok(id) => id,
+            Err(_) => panic!("SmallOrderedVec is full"),
+        }
+    }
+    /// Add an element to the ordered vector. If we ran out of addressable slots, the element gets returned back
+    pub fn try_push_shove(&mut self, elem: T) -> Result<Id32, T> {
+        if self.get_next_id().is_none() {
+            return Err(elem);
+        }
+        let (index, version) = self.slots.push_shove(elem);
+        Ok(Id32::new(index, version))
+    }
+    /// Get the ID of the next element that we will add, if we have an addressable slot left for it
+    pub fn get_next_id(&self) -> Option<Id32> {
+        let (index, version) = self.slots.next_slot();
+        (index < Id32::MAX_INDEX).then(|| Id32::new(index, version))
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id32) -> Option<T> {
+        if id.is_null() {
+            return None;
+        }
+        self.slots.remove(id.index(), id.version() as u32)
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id32) -> Option<&T> {
+        if id.is_null() {
+            return None;
+        }
+        self.slots.get(id.index(), id.version() as u32)
+    }
+    /// Get a mutable reference to an element in the ordered vector
+    pub fn get_mut(&mut self, id: Id32) -> Option<&mut T> {
+        if id.is_null() {
+            return None;
+        }
+        self.slots.get_mut(id.index(), id.version() as u32)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        self.slots.metrics()
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the number of invalid elements in the ordered vector
+    pub fn count_invalid(&self) -> usize {
+        self.slots.count_invalid()
+    }
+    /// Clear the whole ordered vector
+    pub fn clear(&mut self) -> Vec<Option<T>> {
+        self.slots.clear()
+    }
+}
+
+/// Iter magic
+impl<T> SmallOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id32, &T)> {
+        self.slots
+            .iter()
+            .map(|(index, version, val)| (Id32::new(index, version as u8), val))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id32, &mut T)> {
+        self.slots
+            .iter_mut()
+            .map(|(index, version, val)| (Id32::new(index, version as u8), val))
+    }
+}
+
+/// Traits
+impl<T> Index<Id32> for SmallOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id32) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> IndexMut<Id32> for SmallOrderedVec<T> {
+    fn index_mut(&mut self, index: Id32) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
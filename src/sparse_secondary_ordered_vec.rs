@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    ordered_vec::OrderedVec,
+    utils::{Id, IdCodec},
+};
+
+/// A secondary ordered vector that stores its data inside a HashMap instead of a Vec
+/// This is better when only a few of the IDs of the primary ordered vector actually have some data attached to them
+pub struct SparseSecondaryOrderedVec<U> {
+    /// The data and the version of the ID that it was inserted with, indexed by the index of that ID
+    pub(crate) map: HashMap<usize, (u32, U)>,
+    /// The codec of the primary ordered vector, used to unpack its IDs
+    pub(crate) codec: IdCodec,
+}
+
+impl<U> Clone for SparseSecondaryOrderedVec<U>
+where
+    U: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<U> Debug for SparseSecondaryOrderedVec<U>
+where
+    U: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SparseSecondaryOrderedVec")
+            .field("map", &self.map)
+            .finish()
+    }
+}
+
+impl<U> Default for SparseSecondaryOrderedVec<U> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<U> SparseSecondaryOrderedVec<U> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new sparse secondary ordered vector that understands the IDs of a specific primary ordered vector
+    pub fn for_primary<T>(primary: &OrderedVec<T>) -> Self {
+        Self {
+            map: HashMap::new(),
+            codec: primary.codec(),
+        }
+    }
+    /// Insert some data for an ID. If the slot contained data for an older ID, it gets overwritten
+    /// This returns the old data only if it was inserted with the same ID
+    pub fn insert(&mut self, id: Id, data: U) -> Option<U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let old = self.map.insert(index, (version, data))?;
+        (old.0 == version).then_some(old.1)
+    }
+    /// Remove the data of an ID
+    pub fn remove(&mut self, id: Id) -> Option<U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        if self.map.get(&index)?.0 != version {
+            return None;
+        }
+        self.map.remove(&index).map(|(_, data)| data)
+    }
+    /// Get a reference to the data of an ID
+    pub fn get(&self, id: Id) -> Option<&U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        match self.map.get(&index)? {
+            (stored, data) if *stored == version => Some(data),
+            _ => None,
+        }
+    }
+    /// Get a mutable reference to the data of an ID
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut U> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        match self.map.get_mut(&index)? {
+            (stored, data) if *stored == version => Some(data),
+            _ => None,
+        }
+    }
+    /// Check if we have some data for an ID
+    pub fn contains(&self, id: Id) -> bool {
+        self.get(id).is_some()
+    }
+    /// Get the number of IDs that have some data. This might include data of IDs that were removed from the primary
+    pub fn count(&self) -> usize {
+        self.map.len()
+    }
+    /// Clear the whole sparse secondary ordered vector
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+/// Iter magic
+impl<U> SparseSecondaryOrderedVec<U> {
+    /// Get an iterator over the data, with the ID that it was inserted with. This is not sorted by index
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &U)> {
+        let codec = self.codec;
+        self.map
+            .iter()
+            .map(move |(index, (version, data))| (codec.encode(*index, *version), data))
+    }
+    /// Get a mutable iterator over the data, with the ID that it was inserted with. This is not sorted by index
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut U)> {
+        let codec = self.codec;
+        self.map
+            .iter_mut()
+            .map(move |(index, (version, data))| (codec.encode(*index, *version), data))
+    }
+}
+
+/// Traits
+impl<U> Index<Id> for SparseSecondaryOrderedVec<U> {
+    type Output = U;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<U> IndexMut<Id> for SparseSecondaryOrderedVec<U> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
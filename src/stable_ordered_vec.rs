@@ -0,0 +1,242 @@
+use std::{
+    fmt::Debug,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    invariant::{check_free_list, check_len, InvariantError},
+    metrics::{largest_free_run, Metrics},
+    slots::Slots,
+    utils::{Id, IdCodec},
+};
+
+/// An ordered vector whose IDs go through a translation table, so it can be defragmented without invalidating any of them
+/// Fetching an element costs one extra lookup, since the ID points to a slot that stores the position of the value
+pub struct StableOrderedVec<T> {
+    /// The values, with holes where elements were removed
+    pub(crate) values: Vec<Option<T>>,
+    /// For each value, the index of the slot that points to it. This is garbage for the holes
+    pub(crate) owners: Vec<usize>,
+    /// The positions of the holes inside the values, so whenever we add a new element, we will add it there
+    pub(crate) holes: Vec<usize>,
+    /// The slots containing the position of each value and their versions
+    pub(crate) slots: Slots<usize, u32>,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+}
+
+impl<T> Clone for StableOrderedVec<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            owners: self.owners.clone(),
+            holes: self.holes.clone(),
+            slots: self.slots.clone(),
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Debug for StableOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StableOrderedVec")
+            .field("values", &self.values)
+            .field("holes", &self.holes)
+            .field("missing", &self.slots.missing)
+            .finish()
+    }
+}
+
+impl<T> Default for StableOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            owners: Vec::new(),
+            holes: Vec::new(),
+            slots: Slots::default(),
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<T> StableOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new stable ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this stable ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Add an element to the stable ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let position = self.holes.pop().unwrap_or(self.values.len());
+        let (index, version) = self.slots.push_shove(position);
+        if position == self.values.len() {
+            self.values.push(Some(elem));
+            self.owners.push(index);
+        } else {
+            self.values[position] = Some(elem);
+            self.owners[position] = index;
+        }
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec[index].1 = version;
+        self.codec.encode(index, version)
+    }
+    /// Get the ID of the next element that we will add
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
+    }
+    /// Remove an element that is contained in the vec. This leaves a hole behind, until it gets re-used or until we defragment
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let position = self.slots.remove(index, version)?;
+        self.holes.push(position);
+        self.values[position].take()
+    }
+    /// Get a reference to an element in the stable ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let position = *self.slots.get(index, version)?;
+        self.values.get(position)?.as_ref()
+    }
+    /// Get a mutable reference to an element in the stable ordered vector
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let position = *self.slots.get(index, version)?;
+        self.values.get_mut(position)?.as_mut()
+    }
+    /// Move the values down to fill up all the holes, keeping their order, and free the memory that is left over
+    /// Only the translation table gets updated, so every ID that was valid before stays valid. This returns the number of holes that got removed
+    pub fn defragment(&mut self) -> usize {
+        let removed = self.holes.len();
+        let mut write = 0;
+        for read in 0..self.values.len() {
+            if self.values[read].is_none() {
+                continue;
+            }
+            if read != write {
+                self.values.swap(read, write);
+                let owner = self.owners[read];
+                self.owners[write] = owner;
+                self.slots.vec[owner].0 = Some(write);
+            }
+            write += 1;
+        }
+        self.values.truncate(write);
+        self.owners.truncate(write);
+        self.values.shrink_to_fit();
+        self.owners.shrink_to_fit();
+        self.holes = Vec::new();
+        removed
+    }
+    /// Check the structural invariants of the stable ordered vector, like the free lists matching up with the empty slots and holes
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()?;
+        check_free_list(
+            self.values.iter().map(|val| Some(val.is_some())),
+            self.holes.iter().copied(),
+        )?;
+        check_len("owners", self.values.len(), self.owners.len())?;
+        check_len(
+            "values",
+            self.slots.count(),
+            self.values.len() - self.holes.len(),
+        )?;
+        // Each slot must point to a value that is owned by that same slot
+        for (index, _, &position) in self.slots.iter() {
+            if !matches!(self.values.get(position), Some(Some(_))) || self.owners[position] != index
+            {
+                return Err(InvariantError::BrokenBacklink { index });
+            }
+        }
+        Ok(())
+    }
+    /// Get the memory and fragmentation statistics of the stable ordered vector. The holes are those of the values, not those of the translation table
+    pub fn metrics(&self) -> Metrics {
+        let slots = self.slots.metrics();
+        Metrics {
+            bytes_allocated: slots.bytes_allocated
+                + self.values.capacity() * std::mem::size_of::<Option<T>>()
+                + (self.owners.capacity() + self.holes.capacity()) * std::mem::size_of::<usize>(),
+            live: self.count(),
+            holes: self.count_holes(),
+            largest_free_run: largest_free_run(self.values.iter().map(Option::is_some)),
+            reuses: slots.reuses,
+        }
+    }
+    /// Get the number of valid elements in the stable ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the number of holes inside the values, which defragment() would get rid of
+    pub fn count_holes(&self) -> usize {
+        self.holes.len()
+    }
+    /// Clear the whole stable ordered vector
+    pub fn clear(&mut self) -> Vec<Option<T>> {
+        self.slots.clear();
+        self.owners.clear();
+        self.holes.clear();
+        std::mem::take(&mut self.values)
+    }
+}
+
+/// Iter magic
+impl<T> StableOrderedVec<T> {
+    // Get the ID of the value at a specific position
+    fn id_at(codec: IdCodec, slots: &Slots<usize, u32>, owner: usize) -> Id {
+        codec.encode(owner, slots.vec[owner].1)
+    }
+    /// Get an iterator over the valid elements in the order of their position, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.owners
+            .iter()
+            .zip(self.values.iter())
+            .filter_map(|(&owner, val)| {
+                Some((Self::id_at(self.codec, &self.slots, owner), val.as_ref()?))
+            })
+    }
+    /// Get a mutable iterator over the valid elements in the order of their position, but with the ID of each element
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let codec = self.codec;
+        let slots = &self.slots;
+        self.owners
+            .iter()
+            .zip(self.values.iter_mut())
+            .filter_map(move |(&owner, val)| {
+                Some((Self::id_at(codec, slots, owner), val.as_mut()?))
+            })
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for StableOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> IndexMut<Id> for StableOrderedVec<T> {
+    fn index_mut(&mut self, index: Id) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
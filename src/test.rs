@@ -1,13 +1,19 @@
 #[cfg(test)]
+#[allow(clippy::module_inception, clippy::identity_op)]
 pub mod test {
-    use crate::{shareable_ordered_vec::ShareableOrderedVec, simple::*};
+    use crate::{
+        shareable::{FixedConcurrentOrderedVec, ShareableOrderedVec, Unreserved},
+        simple::*,
+        utils::{Id, Id32, ReusePolicy},
+    };
     use std::{
         collections::HashMap,
         sync::{Arc, RwLock},
         thread::JoinHandle,
     };
-    // Test the speed of the ordered vec
+    // Test the speed of the ordered vec. The instrumentation makes every operation linear, so this gets way too slow with it
     #[test]
+    #[cfg_attr(feature = "debug-validate", ignore)]
     pub fn speed_test() {
         const N: usize = 100_000;
         let mut hashmap = HashMap::<usize, u64>::default();
@@ -105,7 +111,7 @@ pub mod test {
         //dbg!(vec.push_shove(2_u64 | (0_u64 << 32)));
 
         for (id, elem) in vec.iter() {
-            assert_eq!(id, *elem);
+            assert_eq!(u64::from(id), *elem);
         }
 
         // My drain test
@@ -118,9 +124,9 @@ pub mod test {
         vec.remove(last).unwrap();
         vec.push_shove(4);
         let mut removed = vec.my_drain(|_index, val| val % 2 == 0);
-        assert_eq!(removed.next(), Some((0_u64 | (0_u64 << 32), 0)));
-        assert_eq!(removed.next(), Some((2_u64 | (0_u64 << 32), 2)));
-        assert_eq!(removed.next(), Some((4_u64 | (1_u64 << 32), 4)));
+        assert_eq!(removed.next(), Some((Id::new(0, 0), 0)));
+        assert_eq!(removed.next(), Some((Id::new(2, 0), 2)));
+        assert_eq!(removed.next(), Some((Id::new(4, 1), 4)));
     }
     // Clearing test
     #[test]
@@ -152,13 +158,13 @@ pub mod test {
     pub fn id_test() {
         let mut vec = OrderedVec::<String>::default();
         let bob_id = vec.push_shove("Bob".to_string());
-        assert_eq!(bob_id, 0);
-        assert_eq!(vec.get_next_id(), 1_u64);
+        assert_eq!(bob_id, Id::new(0, 0));
+        assert_eq!(vec.get_next_id(), Id::new(1, 0));
         assert!(vec.remove(bob_id).is_some());
         let john_id = vec.get_next_id(); // Index: 0, Version: 1
         let john_id2 = vec.push_shove("John".to_string()); // Index: 0, Version: 1
         assert_eq!(john_id, john_id2);
-        assert_eq!(john_id2, (0_u64 | (1_u64 << 32)))
+        assert_eq!(john_id2, Id::new(0, 1))
     }
     // ID test but for the unversionned version
     #[test]
@@ -177,11 +183,11 @@ pub mod test {
     #[test]
     pub fn shareable_test() {
         let mut vec = ShareableOrderedVec::<String>::default();
-        vec.insert(0, "Bob".to_string());
-        vec.remove(0);
-        vec.insert(0_u64 | (1_u64 << 32), "Bob".to_string());
-        vec.insert(2, "John".to_string());
-        vec.insert(4, "Lina".to_string());
+        vec.insert(Id::new(0, 0), "Bob".to_string()).unwrap();
+        vec.remove(Id::new(0, 0));
+        vec.insert(Id::new(0, 1), "Bob".to_string()).unwrap();
+        vec.insert(Id::new(2, 0), "John".to_string()).unwrap();
+        vec.insert(Id::new(4, 0), "Lina".to_string()).unwrap();
         /*
          */
         // +-------+--------+
@@ -195,7 +201,7 @@ pub mod test {
         // +-------+--------+
         //dbg!(&vec);
         // Make a simple channel so we can receive at what location we must insert the elements
-        let (tx, rx) = std::sync::mpsc::channel::<(u64, String)>();
+        let (tx, rx) = std::sync::mpsc::channel::<(Id, String)>();
 
         let tx = tx;
         let arc = Arc::new(RwLock::new(vec));
@@ -224,7 +230,7 @@ pub mod test {
 
         // Receive all the messages, and apply them
         for (idx, elem) in rx.try_iter() {
-            vec.insert(idx, elem);
+            vec.insert(idx, elem).unwrap();
         }
         //dbg!(vec);
     }
@@ -232,22 +238,2420 @@ pub mod test {
     #[test]
     pub fn shareable_test2() {
         let mut vec = ShareableOrderedVec::<String>::default();
-        vec.insert(0, "Bob".to_string());
-        vec.insert(1, "John".to_string());
-        vec.insert(2, "Lina".to_string());
+        vec.insert(Id::new(0, 0), "Bob".to_string()).unwrap();
+        vec.insert(Id::new(1, 0), "John".to_string()).unwrap();
+        vec.insert(Id::new(2, 0), "Lina".to_string()).unwrap();
         assert_eq!(vec.count(), 3);
-        vec.remove(1);
+        vec.remove(Id::new(1, 0));
         assert_eq!(vec.count(), 2);
         //dbg!(&vec.missing);
 
         // Ticky part
         let next_id = vec.get_next_id_increment();
-        assert_eq!(next_id, 1 | (1_u64 << 32)); // Versionning moment
+        assert_eq!(next_id, Id::new(1, 1)); // Versionning moment
         let next_id2 = vec.get_next_id_increment();
-        assert_eq!(next_id2, 3);
-        vec.insert(next_id, "Boi".to_string());
-        vec.insert(next_id2, "Moment".to_string());
+        assert_eq!(next_id2, Id::new(3, 0));
+        vec.insert(next_id, "Boi".to_string()).unwrap();
+        vec.insert(next_id2, "Moment".to_string()).unwrap();
+        assert_eq!(vec.count(), 4);
+        assert_eq!(vec.count_invalid(), 0);
+    }
+    // Test the fixed concurrent ordered vec
+    #[test]
+    pub fn fixed_concurrent_test() {
+        let vec = Arc::new(FixedConcurrentOrderedVec::<String>::with_capacity(64));
+        let thread_join_handles = (0..8)
+            .map(|x| {
+                let vec = vec.clone();
+                std::thread::spawn(move || {
+                    (0..8)
+                        .map(|i| vec.push_shove(format!("Number {}", i + x * 8)).unwrap())
+                        .collect::<Vec<Id>>()
+                })
+            })
+            .collect::<Vec<JoinHandle<Vec<Id>>>>();
+        let mut ids = thread_join_handles
+            .into_iter()
+            .flat_map(|x| x.join().unwrap())
+            .collect::<Vec<Id>>();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 64);
+        assert_eq!(vec.count(), 64);
+
+        // We are full, so we must get the element back
+        assert_eq!(vec.push_shove("Full".to_string()), Err("Full".to_string()));
+
+        // Removing frees up a slot, and re-using it bumps the version
+        assert!(vec.remove(ids[5]).is_some());
+        assert!(vec.remove(ids[5]).is_none());
+        assert!(!vec.contains(ids[5]));
+        let id = vec.push_shove("Bob".to_string()).unwrap();
+        assert_eq!(id, Id::new(5, 1));
+        let mut vec = Arc::try_unwrap(vec).unwrap();
+        assert_eq!(vec.get_mut(id).unwrap(), "Bob");
+        assert_eq!(vec.iter_mut().count(), 64);
+    }
+    // Test the pool and its guards
+    #[test]
+    pub fn pool_test() {
+        let pool = Pool::<String>::new();
+        let id = {
+            let mut guard = pool.acquire("Bob".to_string());
+            guard.push_str(" the builder");
+            assert_eq!(*guard, "Bob the builder");
+            guard.id()
+        };
+        // The guard was dropped, so the element got removed
+        assert_eq!(pool.count(), 0);
+        assert!(pool.get(id).is_none());
+
+        // Keep an element alive after its guard
+        let kept = pool.acquire_with(|| "John".to_string()).keep();
+        assert_eq!(*pool.get(kept).unwrap(), "John");
+        assert_eq!(pool.count(), 1);
+        assert_eq!(pool.acquire("Lina".to_string()).take(), "Lina");
+        assert_eq!(pool.release(kept).unwrap(), "John");
+        assert_eq!(pool.count(), 0);
+    }
+    // Test the type erased any ordered vec
+    #[test]
+    pub fn any_test() {
+        let mut vec = AnyOrderedVec::new();
+        let bob = vec.insert_any("Bob".to_string());
+        let number = vec.insert_any(5_u32);
+        assert_eq!(vec.get_downcast::<String>(bob).unwrap(), "Bob");
+        assert!(vec.get_downcast::<u64>(number).is_none());
+        *vec.get_downcast_mut::<u32>(number).unwrap() += 1;
+        assert_eq!(
+            vec.iter_downcast::<u32>().collect::<Vec<_>>(),
+            vec![(number, &6)]
+        );
+
+        // Removing with the wrong type must not remove anything
+        assert!(vec.remove_downcast::<u32>(bob).is_none());
+        assert_eq!(vec.count(), 2);
+        assert_eq!(vec.remove_downcast::<String>(bob).unwrap(), "Bob");
+        assert!(vec.get(bob).is_none());
+    }
+    // Test the type keyed registry
+    #[test]
+    pub fn registry_test() {
+        let mut registry = Registry::new();
+        assert!(!registry.is_registered::<String>());
+        registry.register::<String>();
+        let bob = registry.push("Bob".to_string());
+        let position = registry.push((1.0_f32, 2.0_f32));
+        assert!(registry.is_registered::<(f32, f32)>());
+        assert_eq!(registry.get::<String>(bob).unwrap(), "Bob");
+        assert_eq!(registry.get::<(f32, f32)>(position), Some(&(1.0, 2.0)));
+        assert!(registry.get::<u32>(bob).is_none());
+        for (_, (x, _)) in registry.iter_mut::<(f32, f32)>() {
+            *x += 1.0;
+        }
+        assert_eq!(registry.iter::<(f32, f32)>().count(), 1);
+        assert_eq!(registry.remove::<(f32, f32)>(position), Some((2.0, 2.0)));
+        assert_eq!(registry.unregister::<String>().unwrap().count(), 1);
+        assert_eq!(registry.iter::<String>().count(), 0);
+    }
+    // Removing the same element twice must not free its slot twice
+    #[test]
+    pub fn double_remove_test() {
+        let mut vec = OrderedVec::<i32>::default();
+        let a = vec.push_shove(0);
+        vec.push_shove(1);
+        assert_eq!(vec.remove(a), Some(0));
+        assert_eq!(vec.remove(a), None);
+        assert_eq!(vec.remove_index(a.index()), None);
+        assert_eq!(vec.count_invalid(), 1);
+        let b = vec.push_shove(2);
+        let c = vec.push_shove(3);
+        assert_ne!(b, c);
+        assert_eq!(vec.count(), 3);
+
+        let mut vec = UnversionnedOrderedVec::<i32>::default();
+        let a = vec.push_shove(0);
+        vec.remove(a);
+        vec.remove(a);
+        assert_eq!(vec.count_invalid(), 1);
+    }
+    // Test the C API
+    #[cfg(feature = "ffi")]
+    #[test]
+    pub fn ffi_test() {
+        use crate::ffi::*;
+        unsafe {
+            let handle = ov_new(
+                std::mem::size_of::<[u32; 3]>(),
+                std::mem::align_of::<[u32; 3]>(),
+            );
+            let a = ov_push(handle, [1_u32, 2, 3].as_ptr() as *const u8);
+            let b = ov_push(handle, [4_u32, 5, 6].as_ptr() as *const u8);
+            assert_eq!(ov_count(handle), 2);
+            assert_eq!(*(ov_get(handle, b) as *const [u32; 3]), [4, 5, 6]);
+            let mut out = [0_u32; 3];
+            assert!(ov_remove(handle, a, out.as_mut_ptr() as *mut u8));
+            assert_eq!(out, [1, 2, 3]);
+            assert!(!ov_remove(handle, a, std::ptr::null_mut()));
+            assert!(ov_get(handle, a).is_null());
+            ov_free(handle);
+        }
+        assert!(ov_new(4, 3).is_null());
+    }
+    // Test the ID newtype
+    #[test]
+    pub fn id_newtype_test() {
+        let id = Id::new(3, 2);
+        assert_eq!(id.index(), 3);
+        assert_eq!(id.version(), 2);
+        assert_eq!(format!("{:?}", id), "Id(index=3, version=2)");
+        assert_eq!(Id::from(u64::from(id)), id);
+        assert_eq!(u64::from(id), 3 | (2_u64 << 32));
+        assert!(Id::new(4, 0) < Id::new(3, 1));
+    }
+    // The null ID must never resolve to an element
+    #[test]
+    pub fn null_id_test() {
+        let null = Id::null();
+        assert!(null.is_null());
+        assert!(!Id::new(0, 0).is_null());
+        assert_eq!(format!("{:?}", Id::NULL), "Id(null)");
+        let mut vec = OrderedVec::<i32>::default();
+        vec.push_shove(1);
+        assert!(vec.get(Id::NULL).is_none());
+        assert!(vec.get_mut(Id::NULL).is_none());
+        assert!(vec.remove(Id::NULL).is_none());
+        let mut vec = ShareableOrderedVec::<i32>::default();
+        vec.insert(Id::new(0, 0), 1).unwrap();
+        assert!(vec.get(Id::NULL).is_none());
+        assert!(vec.remove(Id::NULL).is_none());
+    }
+    // Test the compact IDs and the small ordered vec
+    #[test]
+    pub fn small_test() {
+        let id = Id32::new(0xFF_FFFE, 7);
+        assert_eq!(id.index(), 0xFF_FFFE);
+        assert_eq!(id.version(), 7);
+        assert_eq!(u32::from(id), 0xFF_FFFE | (7 << 24));
+        assert!(!id.is_null());
+        assert_eq!(std::mem::size_of::<Id32>(), 4);
+
+        let mut vec = SmallOrderedVec::<&str>::new();
+        let bob = vec.push_shove("Bob");
+        assert_eq!(bob, Id32::new(0, 0));
+        vec.remove(bob);
+        // Versions wrap around after 256 re-uses
+        for _ in 0..255 {
+            let id = vec.push_shove("John");
+            vec.remove(id);
+        }
+        assert_eq!(vec.get_next_id(), Some(Id32::new(0, 0)));
+        let lina = vec.push_shove("Lina");
+        assert_eq!(vec[lina], "Lina");
+        assert!(vec.get(Id32::NULL).is_none());
+        assert_eq!(vec.iter().count(), 1);
+    }
+    // Test printing and parsing IDs
+    #[test]
+    pub fn id_string_test() {
+        let id = Id::new(3, 2);
+        assert_eq!(id.to_string(), "3:2");
+        assert_eq!("3:2".parse::<Id>(), Ok(id));
+        assert_eq!(" 3 : 2 ".parse::<Id>(), Ok(id));
+        assert_eq!(Id::NULL.to_string(), "null");
+        assert_eq!("null".parse::<Id>(), Ok(Id::NULL));
+        assert!("3".parse::<Id>().is_err());
+        assert!("3:x".parse::<Id>().is_err());
+        assert!("4294967296:0".parse::<Id>().is_err());
+        assert_eq!("5:255".parse::<Id32>(), Ok(Id32::new(5, 255)));
+        assert!("5:256".parse::<Id32>().is_err());
+        assert_eq!(Id32::new(5, 1).to_string(), "5:1");
+    }
+    // IDs can be built in const contexts
+    #[test]
+    pub fn const_id_test() {
+        use crate::utils::{from_id, to_id, IndexPair};
+        const PLAYER: Id = Id::new(1, 4);
+        const PACKED: u64 = to_id(IndexPair::new(1, 4));
+        const PAIR: IndexPair = from_id(PACKED);
+        assert_eq!(PLAYER.to_u64(), PACKED);
+        assert_eq!(PAIR, IndexPair::new(1, 4));
+        let mut set = std::collections::HashSet::new();
+        set.insert(PAIR);
+        assert!(set.contains(&IndexPair::new(1, 4)));
+    }
+    // Packing and unpacking IDs must round trip over the whole range
+    #[test]
+    pub fn id_round_trip_test() {
+        use crate::utils::{from_id, from_id32, to_id, to_id32, IndexPair};
+        // Simple xorshift so we don't need an external crate
+        let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let edges = [0, 1, u32::MAX / 2, u32::MAX - 1, u32::MAX];
+        let pairs = edges
+            .iter()
+            .flat_map(|&index| edges.iter().map(move |&version| (index, version)))
+            .chain((0..10_000).map(|_| {
+                let x = next();
+                (x as u32, (x >> 32) as u32)
+            }));
+        for (index, version) in pairs {
+            let pair = IndexPair { index, version };
+            let id = to_id(pair);
+            assert_eq!(from_id(id), pair);
+            assert_eq!(to_id(from_id(id)), id);
+            assert_eq!(id, ((version as u64) << 32) | index as u64);
+            let id = Id::from(pair);
+            assert_eq!((id.index(), id.version()), (index as usize, version));
+
+            // The compact IDs only keep the lower bits
+            let small = from_id32(to_id32(pair));
+            assert_eq!(small.index, index & 0xFF_FFFF);
+            assert_eq!(small.version, version & 0xFF);
+        }
+    }
+    // Test the large index codec
+    #[test]
+    pub fn codec_test() {
+        use crate::utils::IdCodec;
+        let codec = IdCodec::LARGE;
+        assert_eq!(codec.index_bits(), 48);
+        assert_eq!(codec.version_bits(), 16);
+        let id = codec.encode(1 << 40, 3);
+        assert_eq!(codec.decode(id), (1 << 40, 3));
+        assert_eq!(IdCodec::DEFAULT.encode(7, 2), Id::new(7, 2));
+
+        // Versions wrap around at 16 bits
+        let mut vec = OrderedVec::<i32>::with_codec(IdCodec::LARGE);
+        let first = vec.push_shove(0);
+        vec.remove(first);
+        for _ in 0..u16::MAX {
+            let id = vec.push_shove(1);
+            vec.remove(id);
+        }
+        assert_eq!(vec.get_next_id(), codec.encode(0, 0));
+        let id = vec.push_shove(2);
+        assert_eq!(codec.decode(id), (0, 0));
+        assert_eq!(vec.get(id), Some(&2));
+        assert_eq!(vec.iter().next(), Some((id, &2)));
+
+        let mut vec = ShareableOrderedVec::<i32>::with_codec(IdCodec::LARGE);
+        let id = vec.get_next_id_increment();
+        vec.insert(id, 5).unwrap();
+        assert_eq!(vec.get(codec.encode(0, 0)), Some(&5));
+    }
+    // IDs from one tagged collection must not resolve in another
+    #[test]
+    pub fn tag_test() {
+        use crate::utils::IdCodec;
+        let codec = IdCodec::DEFAULT.with_tag_bits(8);
+        assert_eq!(codec.version_bits(), 24);
+        let mut meshes = OrderedVec::<&str>::with_codec(codec);
+        let mut textures = OrderedVec::<&str>::with_codec(codec);
+        assert_ne!(meshes.codec().tag(), textures.codec().tag());
+        let mesh = meshes.push_shove("Cube");
+        let texture = textures.push_shove("Bricks");
+        assert_eq!(meshes.codec().tag_of(mesh), meshes.codec().tag());
+        assert_eq!(meshes.get(mesh), Some(&"Cube"));
+        assert_eq!(textures.get(texture), Some(&"Bricks"));
+        let result = std::panic::catch_unwind(|| textures.get(mesh).is_none());
+        if cfg!(debug_assertions) {
+            assert!(result.is_err());
+        } else {
+            assert!(result.unwrap());
+        }
+        assert_eq!(meshes.remove(mesh), Some("Cube"));
+    }
+    // Test the typed handles
+    #[cfg(feature = "typed-handles")]
+    #[test]
+    pub fn handle_test() {
+        use crate::utils::Handle;
+        struct Mesh(&'static str);
+        let mut meshes = OrderedVec::<Mesh>::new();
+        let cube: Handle<Mesh> = meshes.push_handle(Mesh("Cube"));
+        assert_eq!(meshes[cube].0, "Cube");
+        assert_eq!(meshes.get(cube.id()).unwrap().0, "Cube");
+        meshes.get_handle_mut(cube).unwrap().0 = "Sphere";
+        assert_eq!(meshes.iter_handles().next().unwrap().0, cube);
+        assert_eq!(meshes.remove_handle(cube).unwrap().0, "Sphere");
+        assert!(meshes.get_handle(cube).is_none());
+        assert_eq!(format!("{:?}", cube).split('(').nth(1), Some("0:0)"));
+    }
+    // Test the dense ordered vec
+    #[test]
+    pub fn dense_test() {
+        let mut vec = DenseOrderedVec::<i32>::new();
+        let a = vec.push_shove(0);
+        let b = vec.push_shove(1);
+        let c = vec.push_shove(2);
+        assert_eq!(vec.values(), &[0, 1, 2]);
+
+        // Removing swaps the last value in
+        assert_eq!(vec.remove(a), Some(0));
+        assert_eq!(vec.values(), &[2, 1]);
+        assert_eq!(vec[c], 2);
+        assert_eq!(vec[b], 1);
+        assert!(vec.get(a).is_none());
+
+        // The freed slot gets re-used with a new version
+        let d = vec.push_shove(3);
+        assert_eq!(d, Id::new(0, 1));
+        vec[d] += 1;
+        assert_eq!(
+            vec.iter().collect::<Vec<_>>(),
+            vec![(c, &2), (b, &1), (d, &4)]
+        );
+        for (_, val) in vec.iter_mut() {
+            *val *= 10;
+        }
+        assert_eq!(vec.remove(c), Some(20));
+        assert_eq!(vec.remove(c), None);
+        assert_eq!(vec.count(), 2);
+        assert_eq!(vec.values(), &[40, 10]);
+    }
+    // Test the skipfield of the hop ordered vec against a plain ordered vec
+    #[test]
+    pub fn hop_test() {
+        let mut hop = HopOrderedVec::<u32>::new();
+        let mut plain = OrderedVec::<u32>::new();
+        let mut ids = Vec::new();
+        let mut state = 12345_u32;
+        for i in 0..2000 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            if state.is_multiple_of(3) && !ids.is_empty() {
+                let (a, b) = ids.swap_remove((state >> 8) as usize % ids.len());
+                assert_eq!(hop.remove(a), plain.remove(b));
+            } else {
+                ids.push((hop.push_shove(i), plain.push_shove(i)));
+            }
+            // Check that the skipfield matches up with the occupancy of the slots
+            let mut index = 0;
+            while index < hop.skip.len() {
+                let len = hop.skip[index];
+                if len == 0 {
+                    assert!(hop.slots.vec[index].0.is_some());
+                    index += 1;
+                    continue;
+                }
+                assert!(hop.slots.vec[index..(index + len)]
+                    .iter()
+                    .all(|(val, _)| val.is_none()));
+                assert!(index + len == hop.skip.len() || hop.skip[index + len] == 0);
+                assert_eq!(hop.skip[index + len - 1], len);
+                for j in 1..(len - 1) {
+                    assert_eq!(hop.skip[index + j], j + 1);
+                }
+                index += len;
+            }
+        }
+        assert_eq!(
+            hop.iter().map(|(id, val)| (id, *val)).collect::<Vec<_>>(),
+            plain.iter().map(|(id, val)| (id, *val)).collect::<Vec<_>>()
+        );
+        for (_, val) in hop.iter_mut() {
+            *val += 1;
+        }
+        assert_eq!(hop.iter().count(), plain.count());
+    }
+    // Test attaching data to the IDs of another ordered vec
+    #[test]
+    pub fn secondary_test() {
+        let mut primary = OrderedVec::<&str>::new();
+        let mut healths = SecondaryOrderedVec::<u32>::for_primary(&primary);
+        let a = primary.push_shove("a");
+        let b = primary.push_shove("b");
+        assert_eq!(healths.insert(a, 10), None);
+        assert_eq!(healths.insert(b, 20), None);
+        assert_eq!(healths.insert(b, 25), Some(20));
+        assert_eq!(healths[a], 10);
+        assert!(healths.contains(b));
+
+        // Stale IDs don't see the data of the new element that re-uses their slot
+        primary.remove(a);
+        let c = primary.push_shove("c");
+        assert_eq!(c.index(), a.index());
+        assert_eq!(healths.get(c), None);
+        assert_eq!(healths.insert(c, 30), None);
+        assert_eq!(healths.get(a), None);
+        assert_eq!(healths.remove(a), None);
+        healths[c] += 1;
+        assert_eq!(healths.iter().collect::<Vec<_>>(), vec![(c, &31), (b, &25)]);
+        assert_eq!(healths.remove(b), Some(25));
+        assert_eq!(healths.count(), 1);
+    }
+    // Test the hash-backed secondary ordered vec
+    #[test]
+    pub fn sparse_secondary_test() {
+        let mut primary = OrderedVec::<u32>::new();
+        let ids = (0..100).map(|x| primary.push_shove(x)).collect::<Vec<_>>();
+        let mut tags = SparseSecondaryOrderedVec::<&str>::for_primary(&primary);
+        assert_eq!(tags.insert(ids[7], "seven"), None);
+        assert_eq!(tags.insert(ids[99], "last"), None);
+        assert_eq!(tags.insert(ids[7], "sept"), Some("seven"));
+        assert_eq!(tags.count(), 2);
+        assert_eq!(tags[ids[7]], "sept");
+        assert!(!tags.contains(ids[8]));
+
+        // Stale IDs get rejected
+        primary.remove(ids[99]);
+        let new = primary.push_shove(100);
+        assert_eq!(tags.get(new), None);
+        assert_eq!(tags.remove(new), None);
+        assert_eq!(tags.insert(new, "new"), None);
+        assert_eq!(tags.get(ids[99]), None);
+        let mut all = tags.iter().collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, vec![(ids[7], &"sept"), (new, &"new")]);
+        assert_eq!(tags.remove(ids[7]), Some("sept"));
+        assert_eq!(tags.count(), 1);
+    }
+    // Test joining a primary ordered vec with its secondaries
+    #[test]
+    pub fn join_test() {
+        let mut positions = OrderedVec::<i32>::new();
+        let mut velocities = SecondaryOrderedVec::<i32>::for_primary(&positions);
+        let mut names = SecondaryOrderedVec::<&str>::for_primary(&positions);
+        let a = positions.push_shove(0);
+        let b = positions.push_shove(10);
+        let c = positions.push_shove(20);
+        velocities.insert(a, 1);
+        velocities.insert(c, 3);
+        names.insert(c, "c");
+        names.insert(b, "b");
+
+        // Only A and C have a velocity
+        for (_, pos, vel) in crate::join!(positions.iter_mut(), velocities.iter()) {
+            *pos += *vel;
+        }
+        assert_eq!(positions[a], 1);
+        assert_eq!(positions[b], 10);
+        assert_eq!(positions[c], 23);
+
+        // Old versions don't get joined with the new element in the same slot
+        positions.remove(a);
+        let d = positions.push_shove(100);
+        names.insert(d, "d");
+        let joined = crate::join!(positions.iter(), velocities.iter(), names.iter())
+            .map(|(id, pos, vel, name)| (id, *pos, *vel, *name))
+            .collect::<Vec<_>>();
+        assert_eq!(joined, vec![(c, 23, 3, "c")]);
+        let joined = crate::join!(positions.iter(), names.iter())
+            .map(|(id, _, name)| (id, *name))
+            .collect::<Vec<_>>();
+        assert_eq!(joined, vec![(d, "d"), (b, "b"), (c, "c")]);
+    }
+    // Test the change ticks of the tracked ordered vec
+    #[test]
+    pub fn tracked_test() {
+        let mut vec = TrackedOrderedVec::<i32>::new();
+        let a = vec.push_shove(0);
+        let b = vec.push_shove(1);
+        let seen = vec.tick();
+        vec.advance_tick();
+
+        // Nothing changed yet
+        assert_eq!(vec.iter_changed_since(seen).count(), 0);
+        *vec.get_mut(b).unwrap() += 1;
+        let c = vec.push_shove(2);
+        let changed = vec
+            .iter_changed_since(seen)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(changed, vec![b, c]);
+        let added = vec
+            .iter_added_since(seen)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(added, vec![c]);
+        assert_eq!(
+            vec.ticks(b),
+            Some(Ticks {
+                added: 0,
+                changed: 1
+            })
+        );
+
+        // Slots that get re-used have fresh ticks
+        let seen = vec.tick();
+        vec.advance_tick();
+        vec.remove(a);
+        assert_eq!(vec.ticks(a), None);
+        let d = vec.push_shove(3);
+        assert_eq!(vec.insert(c, 20), Some(2));
+        let changed = vec
+            .iter_changed_since(seen)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(changed, vec![d, c]);
+        vec.advance_tick();
+        vec.iter_mut().for_each(|_| {});
+        assert_eq!(vec.iter_changed_since(2).count(), 3);
+    }
+    // Test replicating an ordered vec using snapshots and patches
+    #[test]
+    pub fn patch_test() {
+        let mut server = OrderedVec::<i32>::new();
+        let a = server.push_shove(0);
+        let b = server.push_shove(1);
+        let c = server.push_shove(2);
+        let mut client = OrderedVec::<i32>::new();
+        let empty = client.snapshot();
+        assert!(client.apply_patch(diff(&empty, &server.snapshot())));
+        assert_eq!(
+            client.iter().collect::<Vec<_>>(),
+            server.iter().collect::<Vec<_>>()
+        );
+
+        // Update, remove, and re-use a slot
+        let old = server.snapshot();
+        server[b] = 10;
+        server.remove(c);
+        server.remove(a);
+        let d = server.push_shove(3);
+        let e = server.push_shove(4);
+        let new = server.snapshot();
+        let patch = diff(&old, &new);
+        assert_eq!(
+            patch.changes,
+            vec![
+                Change::Remove(a),
+                Change::Insert(d, 3),
+                Change::Update(b, 10),
+                Change::Remove(c),
+                Change::Insert(e, 4),
+            ]
+        );
+        assert!(client.apply_patch(patch));
+        assert_eq!(
+            client.iter().collect::<Vec<_>>(),
+            new.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(client.count(), 3);
+        assert_eq!(client.get(a), None);
+
+        // The client has the same free slots as the server, but not necessarily in the same order
+        let mut free = client.iter_invalid().copied().collect::<Vec<_>>();
+        let mut expected = server.iter_invalid().copied().collect::<Vec<_>>();
+        free.sort();
+        expected.sort();
+        assert_eq!(free, expected);
+        assert!(diff(&new, &server.snapshot()).is_empty());
+        assert!(!client.apply_patch(Patch {
+            changes: vec![Change::Remove(a)]
+        }));
+    }
+    // Test the lazy copies of the copy-on-write ordered vec
+    #[test]
+    pub fn cow_test() {
+        let mut vec = CowOrderedVec::<String>::new();
+        let ids = (0..200)
+            .map(|x| vec.push_shove(x.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec.shared_chunks(), 0);
+
+        // Cloning shares everything
+        let mut copy = vec.clone();
+        assert_eq!(copy.shared_chunks(), 4);
+        copy[ids[10]].push('!');
+        assert_eq!(copy.shared_chunks(), 3);
+        assert_eq!(vec[ids[10]], "10");
+        assert_eq!(copy[ids[10]], "10!");
+
+        // Removing and re-using slots only affects the copy
+        assert_eq!(copy.remove(ids[150]), Some("150".to_string()));
+        let id = copy.push_shove("new".to_string());
+        assert_eq!(id, Id::new(150, 1));
+        assert_eq!(copy.get(ids[150]), None);
+        assert_eq!(vec[ids[150]], "150");
+        assert_eq!(vec.get(id), None);
+        assert_eq!(copy.shared_chunks(), 2);
+        assert_eq!(vec.count(), 200);
+        assert_eq!(copy.count(), 200);
+        assert_eq!(vec.get_next_id(), Id::new(200, 0));
+
+        // Mutably iterating copies the rest
+        copy.iter_mut().for_each(|(_, val)| val.push('?'));
+        assert_eq!(copy.shared_chunks(), 0);
+        assert_eq!(vec.iter().filter(|(_, val)| val.ends_with('?')).count(), 0);
+        assert_eq!(copy.iter().last(), Some((ids[199], &"199?".to_string())));
+    }
+    // Test the inline fixed capacity ordered vec
+    #[test]
+    pub fn fixed_test() {
+        let mut vec = FixedOrderedVec::<String, 3>::new();
+        let a = vec.push_shove("a".to_string()).unwrap();
+        let b = vec.push_shove("b".to_string()).unwrap();
+        let c = vec.push_shove("c".to_string()).unwrap();
+        assert!(vec.is_full());
+        assert_eq!(vec.get_next_id(), None);
+        assert_eq!(vec.push_shove("d".to_string()), Err("d".to_string()));
+
+        // Free a slot and re-use it
+        assert_eq!(vec.remove(b), Some("b".to_string()));
+        assert_eq!(vec.remove(b), None);
+        assert_eq!(vec.get_next_id(), Some(Id::new(1, 1)));
+        let d = vec.push_shove("d".to_string()).unwrap();
+        assert_eq!(d, Id::new(1, 1));
+        assert_eq!(vec.get(b), None);
+        vec[d].push('!');
+        assert_eq!(
+            vec.iter()
+                .map(|(id, val)| (id, val.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(a, "a"), (d, "d!"), (c, "c")]
+        );
+        vec.clear();
+        assert_eq!(vec.count(), 0);
+        assert_eq!(vec.capacity(), 3);
+        assert_eq!(vec.push_shove("e".to_string()), Ok(a));
+    }
+    // Test that the paged ordered vec never moves its elements
+    #[test]
+    pub fn paged_test() {
+        let mut vec = PagedOrderedVec::<u64>::new();
+        let first = vec.push_shove(0);
+        let address = vec.get(first).unwrap() as *const u64;
+        let ids = (1..(PAGE_SIZE as u64 * 3))
+            .map(|x| vec.push_shove(x))
+            .collect::<Vec<_>>();
+        assert_eq!(vec.page_count(), 3);
+        assert_eq!(vec.get(first).unwrap() as *const u64, address);
+        assert_eq!(vec[ids[PAGE_SIZE * 2]], PAGE_SIZE as u64 * 2 + 1);
+
+        // Re-use a slot in the middle page
+        let old = ids[PAGE_SIZE];
+        assert_eq!(vec.remove(old), Some(PAGE_SIZE as u64 + 1));
+        assert_eq!(vec.get_next_id(), Id::new(old.index(), 1));
+        let new = vec.push_shove(7);
+        assert_eq!(vec.get(old), None);
+        vec[new] += 1;
+        assert_eq!(vec[new], 8);
+        assert_eq!(vec.count(), PAGE_SIZE * 3);
+        assert_eq!(vec.iter().nth(old.index()), Some((new, &8)));
+        let sum: u64 = vec.iter_mut().map(|(_, val)| *val).sum();
+        assert_eq!(
+            sum,
+            (0..(PAGE_SIZE as u64 * 3)).sum::<u64>() - (PAGE_SIZE as u64 + 1) + 8
+        );
+        assert_eq!(vec.clear().len(), PAGE_SIZE * 3);
+        assert_eq!(vec.page_count(), 0);
+    }
+    // Test interning and releasing values
+    #[test]
+    pub fn interner_test() {
+        let mut interner = Interner::<String>::new();
+        let (a, count) = interner.intern("hello".to_string());
+        assert_eq!(count, 1);
+        let (b, count) = interner.intern("world".to_string());
+        assert_eq!(count, 1);
+        assert_eq!(interner.intern("hello".to_string()), (a, 2));
+        assert_eq!(interner.count(), 2);
+        assert_eq!(interner[a], "hello");
+        assert_eq!(interner.find(&"world".to_string()), Some(b));
+        assert_eq!(interner.ref_count(a), Some(2));
+
+        // The slot only gets freed when the count reaches zero
+        assert_eq!(interner.release(a), Some(1));
+        assert_eq!(interner.get(a).map(|x| x.as_str()), Some("hello"));
+        assert_eq!(interner.release(a), Some(0));
+        assert_eq!(interner.get(a), None);
+        assert_eq!(interner.release(a), None);
+        assert_eq!(interner.find(&"hello".to_string()), None);
+
+        // Interning it again gives a new ID
+        let (c, count) = interner.intern("hello".to_string());
+        assert_eq!(count, 1);
+        assert_ne!(c, a);
+        assert_eq!(
+            interner
+                .iter()
+                .map(|(id, val, count)| (id, val.as_str(), count))
+                .collect::<Vec<_>>(),
+            vec![(c, "hello", 1), (b, "world", 1)]
+        );
+    }
+    // Test the different slot re-use policies
+    #[test]
+    pub fn reuse_policy_test() {
+        let order = |policy: ReusePolicy| {
+            let mut vec = OrderedVec::<u32>::with_reuse_policy(policy);
+            let ids = (0..6).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+            vec.remove(ids[3]);
+            vec.remove(ids[1]);
+            vec.remove(ids[4]);
+            let next = vec.get_next_id().index();
+            let indices = (0..3)
+                .map(|x| vec.push_shove(x).index())
+                .collect::<Vec<_>>();
+            assert_eq!(next, indices[0]);
+            indices
+        };
+        assert_eq!(order(ReusePolicy::Lifo), vec![4, 1, 3]);
+        assert_eq!(order(ReusePolicy::Fifo), vec![3, 1, 4]);
+        assert_eq!(order(ReusePolicy::LowestIndex), vec![1, 3, 4]);
+
+        // FIFO spreads the re-uses across all the free slots
+        let mut vec = SmallOrderedVec::<u32>::with_reuse_policy(ReusePolicy::Fifo);
+        assert_eq!(vec.reuse_policy(), ReusePolicy::Fifo);
+        let a = vec.push_shove(0);
+        let b = vec.push_shove(1);
+        vec.remove(a);
+        vec.remove(b);
+        let c = vec.push_shove(2);
+        vec.remove(c);
+        assert_eq!(vec.push_shove(3).index(), b.index());
+        let mut vec = UnversionnedOrderedVec::<u32>::with_reuse_policy(ReusePolicy::LowestIndex);
+        (0..4).for_each(|x| {
+            vec.push_shove(x);
+        });
+        vec.remove(2);
+        vec.remove(0);
+        assert_eq!(vec.push_shove(4), 0);
+    }
+    // Test modifying an ordered vec while walking over it
+    #[test]
+    pub fn cursor_test() {
+        let mut vec = OrderedVec::<i32>::new();
+        let ids = (0..6).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[0]);
+
+        // Remove the odd elements and double the even ones
+        let mut cursor = vec.cursor_mut();
+        assert_eq!(cursor.current_id(), Some(ids[1]));
+        let mut removed = Vec::new();
+        while let Some((id, val)) = cursor.current() {
+            if *val % 2 == 1 {
+                removed.push(cursor.remove_current().unwrap());
+            } else {
+                assert_eq!(id, ids[*val as usize]);
+                *val *= 2;
+                cursor.advance();
+            }
+        }
+        assert!(cursor.is_done());
+        assert!(!cursor.advance());
+        assert_eq!(removed, vec![(ids[1], 1), (ids[3], 3), (ids[5], 5)]);
+        assert_eq!(vec.iter().map(|(_, x)| *x).collect::<Vec<_>>(), vec![4, 8]);
+
+        // Elements added behind the cursor don't get visited
+        let mut cursor = vec.cursor_mut();
+        cursor.advance();
+        cursor.advance();
+        let new = cursor.push_shove(10);
+        assert_eq!(cursor.current_id(), None);
+        assert_eq!(vec[new], 10);
+        assert_eq!(vec.count(), 3);
+    }
+    // Test sharing a frozen ordered vec between threads
+    #[test]
+    pub fn frozen_test() {
+        let mut vec = OrderedVec::<u64>::new();
+        let ids = (0..100).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[50]);
+        let frozen = vec.freeze();
+        let threads = (0..4)
+            .map(|_| {
+                let frozen = frozen.clone();
+                std::thread::spawn(move || frozen.iter_elements().sum::<u64>())
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 4950 - 50);
+        }
+        assert_eq!(frozen[ids[10]], 10);
+        assert!(!frozen.contains(ids[50]));
+        assert_eq!(frozen.count(), 99);
+
+        // Can only thaw the last clone
+        let other = frozen.clone();
+        assert_eq!(frozen.share_count(), 2);
+        let frozen = frozen.thaw().unwrap_err();
+        drop(other);
+        let mut vec = frozen.thaw().unwrap();
+        vec.push_shove(0);
+        assert_eq!(vec.count(), 100);
+    }
+    // Test the generational-arena shim
+    #[test]
+    pub fn arena_test() {
+        use crate::arena::{Arena, Index};
+        let mut arena = Arena::<&str>::with_capacity(4);
+        assert!(arena.capacity() >= 4);
+        let a = arena.insert("a");
+        let b = arena.insert_with(|index| {
+            if index.into_raw_parts().0 == 1 {
+                "b"
+            } else {
+                "?"
+            }
+        });
+        assert_eq!(arena[b], "b");
+        assert_eq!(arena.len(), 2);
+        assert_eq!(arena.remove(a), Some("a"));
+        assert!(!arena.contains(a));
+        let c = arena.insert("c");
+        assert_eq!(c, Index::from_raw_parts(0, 1));
+        let (x, y) = arena.get2_mut(b, c);
+        std::mem::swap(x.unwrap(), y.unwrap());
+        assert_eq!((arena[b], arena[c]), ("c", "b"));
+        arena.retain(|index, _| index != b);
+        assert_eq!(arena.iter().collect::<Vec<_>>(), vec![(c, &"b")]);
+        arena.extend(["d", "e"]);
+        assert_eq!(arena.drain().count(), 3);
+        assert!(arena.is_empty());
+        let vec: OrderedVec<&str> = Arena::from_iter(["f"]).into();
+        assert_eq!(vec.count(), 1);
+    }
+    // Test the memory and fragmentation metrics
+    #[test]
+    pub fn metrics_test() {
+        let mut vec = OrderedVec::<u64>::new();
+        let ids = (0..10).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        for id in &ids[2..6] {
+            vec.remove(*id);
+        }
+        vec.remove(ids[8]);
+        let metrics = vec.metrics();
+        assert_eq!(metrics.live, 5);
+        assert_eq!(metrics.holes, 5);
+        assert_eq!(metrics.largest_free_run, 4);
+        assert_eq!(metrics.reuses, 0);
+        assert!(metrics.bytes_allocated >= 10 * std::mem::size_of::<(Option<u64>, u32)>());
+        vec.push_shove(0);
+        vec.push_shove(1);
+        assert_eq!(vec.metrics().reuses, 2);
+        assert_eq!(vec.metrics().largest_free_run, 3);
+
+        // The other collections report the same things
+        let mut fixed = FixedOrderedVec::<u64, 4>::new();
+        let a = fixed.push_shove(0).unwrap();
+        fixed.remove(a);
+        fixed.push_shove(1).unwrap();
+        let metrics = fixed.metrics();
+        assert_eq!(
+            (
+                metrics.live,
+                metrics.holes,
+                metrics.largest_free_run,
+                metrics.reuses
+            ),
+            (1, 3, 3, 1)
+        );
+        let concurrent = FixedConcurrentOrderedVec::<u64>::with_capacity(4);
+        let a = concurrent.push_shove(0).unwrap();
+        concurrent.push_shove(1).unwrap();
+        concurrent.remove(a);
+        concurrent.push_shove(2).unwrap();
+        let metrics = concurrent.metrics();
+        assert_eq!(
+            (metrics.live, metrics.holes, metrics.largest_free_run),
+            (2, 2, 1)
+        );
+        let mut paged = PagedOrderedVec::<u64>::new();
+        paged.push_shove(0);
+        assert_eq!(paged.metrics().live, 1);
+        assert!(paged.metrics().bytes_allocated >= PAGE_SIZE * 8);
+    }
+    // Test exporting the elements as a dense array
+    #[test]
+    pub fn to_dense_test() {
+        let mut vec = OrderedVec::<[f32; 2]>::new();
+        let a = vec.push_shove([0.0, 1.0]);
+        let b = vec.push_shove([2.0, 3.0]);
+        let c = vec.push_shove([4.0, 5.0]);
+        vec.remove(b);
+        let (values, offsets) = vec.to_dense();
+        assert_eq!(values, vec![[0.0, 1.0], [4.0, 5.0]]);
+        assert_eq!(offsets, vec![(a, 0), (c, 1)]);
+        let lookup = offsets.into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(values[lookup[&c] as usize], vec[c]);
+        assert!(!lookup.contains_key(&b));
+    }
+    // Test the stable binary format
+    #[test]
+    pub fn binary_test() {
+        let mut vec = OrderedVec::<u32>::new();
+        let ids = (0..10).map(|x| vec.push_shove(x * 10)).collect::<Vec<_>>();
+        vec.remove(ids[3]);
+        vec.remove(ids[7]);
+        vec.remove(ids[1]);
+        let reused = vec.push_shove(100);
+        let mut bytes = Vec::new();
+        vec.encode(&mut bytes, &LittleEndian).unwrap();
+        assert_eq!(&bytes[..4], b"OVEC");
+        assert_eq!(bytes[4..6], 1u16.to_le_bytes());
+
+        // Everything comes back with the same IDs, and the free list stays in the same order
+        let mut decoded = OrderedVec::<u32>::decode(&mut bytes.as_slice(), &LittleEndian).unwrap();
+        assert_eq!(
+            decoded.iter().collect::<Vec<_>>(),
+            vec.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(decoded[reused], 100);
+        assert_eq!(decoded.get(ids[1]), None);
+        assert_eq!(decoded.get_next_id(), vec.get_next_id());
+        assert_eq!(decoded.push_shove(0), vec.push_shove(0));
+
+        // Broken data gets rejected
+        assert!(OrderedVec::<u32>::decode(&mut &b"nope"[..], &LittleEndian).is_err());
+        let mut future = bytes.clone();
+        future[4] = 2;
+        assert!(OrderedVec::<u32>::decode(&mut future.as_slice(), &LittleEndian).is_err());
+        assert!(OrderedVec::<u32>::decode(&mut &bytes[..bytes.len() - 1], &LittleEndian).is_err());
+    }
+    // Test that pinned elements can't be removed
+    #[test]
+    pub fn pinned_test() {
+        let mut vec = PinnedOrderedVec::<String>::new();
+        let a = vec.push_shove("a".to_string());
+        let b = vec.push_shove("b".to_string());
+        let pin = vec.pin(a).unwrap();
+        let other = pin.clone();
+        assert_eq!(vec.pin_count(a), Some(2));
+        assert_eq!(vec.remove(a), Err(Pinned(a)));
+        assert!(vec.get_mut(a).is_none());
+        assert_eq!(*pin, "a");
+
+        // Once the guards are gone, we can remove it
+        drop(pin);
+        drop(other);
+        vec.get_mut(a).unwrap().push('!');
+        assert_eq!(vec.remove(a), Ok(Some("a!".to_string())));
+        assert_eq!(vec.remove(a), Ok(None));
+
+        // Deferred removal keeps the value alive for the guards
+        let pin = vec.pin(b).unwrap();
+        assert!(vec.remove_deferred(b));
+        assert!(vec.get(b).is_none());
+        let c = vec.push_shove("c".to_string());
+        assert_eq!(c.index(), b.index());
+        assert_eq!(pin.id(), b);
+        assert_eq!(*pin, "b");
+        assert!(vec.pin(b).is_none());
+    }
+    // Test deferring the destruction of removed elements
+    #[test]
+    pub fn reclaim_test() {
+        let mut vec = OrderedVec::<String>::new();
+        let a = vec.push_shove("a".to_string());
+        let b = vec.push_shove("b".to_string());
+        let c = vec.push_shove("c".to_string());
+        assert!(vec.mark_removed(b));
+        assert!(vec.mark_removed(a));
+        assert!(!vec.mark_removed(a));
+        assert_eq!(vec.get(b), None);
+        assert_eq!(vec.count(), 1);
+        assert_eq!(vec.count_marked(), 2);
+        assert_eq!(vec.iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![c]);
+
+        // Reclaim them at the end of the frame
+        let reclaimed = vec.reclaim().collect::<Vec<_>>();
+        assert_eq!(reclaimed, vec![(b, "b".to_string()), (a, "a".to_string())]);
+        assert_eq!(vec.count_marked(), 0);
+        assert_eq!(vec.reclaim().count(), 0);
+    }
+    // Test purging expired elements
+    #[test]
+    pub fn expiring_test() {
+        use std::time::{Duration, Instant};
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        let mut vec = ExpiringOrderedVec::<&str>::new();
+        let a = vec.insert_expiring_at("a", start + second * 3);
+        let b = vec.insert_expiring_at("b", start + second);
+        let forever = vec.push_shove("forever");
+        let c = vec.insert_expiring_at("c", start + second * 2);
+        let d = vec.insert_with_ttl("d", second * 100);
+        assert_eq!(vec.purge_expired(start), vec![]);
+
+        // Expired elements come out in order
+        assert_eq!(
+            vec.purge_expired(start + second * 2),
+            vec![(b, "b"), (c, "c")]
+        );
+        assert_eq!(vec.get(b), None);
+
+        // Refreshing and removing elements skips their old deadlines
+        assert!(vec.set_expiry(a, Some(start + second * 10)));
+        vec.remove(d);
+        assert_eq!(vec.purge_expired(start + second * 5), vec![]);
+        assert_eq!(vec.expires_at(forever), None);
+        assert_eq!(vec.purge_expired(start + second * 1000), vec![(a, "a")]);
+        assert_eq!(vec.iter().collect::<Vec<_>>(), vec![(forever, &"forever")]);
+    }
+    // Test the eviction policies of the bounded ordered vec
+    #[test]
+    pub fn bounded_test() {
+        let mut fifo = BoundedOrderedVec::<u32>::new(2, EvictionPolicy::OldestInsert);
+        let (a, _) = fifo.push_shove(0);
+        let (b, _) = fifo.push_shove(1);
+        fifo.get(a);
+        let (c, evicted) = fifo.push_shove(2);
+        assert_eq!(evicted, Some((a, 0)));
+        assert_eq!(c.index(), a.index());
+        assert_eq!(fifo.iter_eviction_order().collect::<Vec<_>>(), vec![b, c]);
+
+        // The LRU policy keeps the elements that we use
+        let mut lru = BoundedOrderedVec::<u32>::new(3, EvictionPolicy::LeastRecentlyUsed);
+        let (a, _) = lru.push_shove(0);
+        let (b, _) = lru.push_shove(1);
+        let (c, _) = lru.push_shove(2);
+        assert_eq!(lru.get(a), Some(&0));
+        *lru.get_mut(b).unwrap() += 10;
+        assert_eq!(lru.peek(c), Some(&2));
+        assert_eq!(lru.next_evicted(), Some(c));
+        let (d, evicted) = lru.push_shove(3);
+        assert_eq!(evicted, Some((c, 2)));
+        assert_eq!(lru.iter_eviction_order().collect::<Vec<_>>(), vec![a, b, d]);
+        assert_eq!(lru.remove(b), Some(11));
+        assert_eq!(lru.iter_eviction_order().collect::<Vec<_>>(), vec![a, d]);
+        let (e, evicted) = lru.push_shove(4);
+        assert_eq!(evicted, None);
+        assert_eq!(lru.count(), 3);
+        assert_eq!(lru.push_shove(5).1, Some((a, 0)));
+        assert_eq!(lru.iter_eviction_order().count(), 3);
+        assert_eq!(lru[e], 4);
+    }
+    // Test the key to ID mapping of the keyed ordered vec
+    #[test]
+    pub fn keyed_test() {
+        let mut vec = KeyedOrderedVec::<String, u32>::new();
+        let (a, old) = vec.insert("a.png".to_string(), 0);
+        assert_eq!(old, None);
+        let (b, _) = vec.insert("b.png".to_string(), 1);
+        assert_eq!(vec.insert("a.png".to_string(), 2), (a, Some(0)));
+        assert_eq!(vec.get_by_key("a.png"), Some(&2));
+        assert_eq!(vec.get_by_id(b), Some(&1));
+        assert_eq!(vec.id_of("b.png"), Some(b));
+        assert_eq!(vec.key_of(a).map(|x| x.as_str()), Some("a.png"));
+        *vec.get_by_key_mut("b.png").unwrap() += 10;
+        assert_eq!(vec.remove_by_key("b.png"), Some((b, 11)));
+        assert!(!vec.contains_key("b.png"));
+        assert_eq!(vec.get_by_id(b), None);
+        assert_eq!(vec.remove_by_id(a), Some(("a.png".to_string(), 2)));
+        assert_eq!(vec.get_by_key("a.png"), None);
+        assert_eq!(vec.count(), 0);
+    }
+    // Test the tag bitsets and the filtered iteration
+    #[test]
+    pub fn tags_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let a = vec.push_shove(0);
+        let b = vec.push_shove(1);
+        let c = vec.push_shove(2);
+        assert!(vec.set_tag(a, 0));
+        assert!(vec.set_tag(c, 0));
+        assert!(vec.set_tag(c, 5));
+        assert_eq!(vec.tags(b), Some(0));
+        assert_eq!(vec.tags(c), Some(0b100001));
+        let filtered = vec.iter_filtered(1).map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(filtered, vec![a, c]);
+        let filtered = vec
+            .iter_filtered(0b100001)
+            .map(|(_, x)| *x)
+            .collect::<Vec<_>>();
+        assert_eq!(filtered, vec![2]);
+        assert!(vec.unset_tag(c, 5));
+        assert_eq!(vec.iter_filtered(1 << 5).count(), 0);
+
+        // Removed elements lose their tags, even when their slot gets re-used
+        vec.remove(a);
+        assert!(!vec.set_tag(a, 1));
+        assert_eq!(vec.tags(a), None);
+        let d = vec.push_shove(3);
+        assert_eq!(d.index(), a.index());
+        assert_eq!(vec.tags(d), Some(0));
+        assert_eq!(
+            vec.iter_filtered(1).map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![c]
+        );
+    }
+    // Test committing and discarding transactions
+    #[test]
+    pub fn transaction_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let a = vec.push_shove(0);
+        let b = vec.push_shove(1);
+
+        // Dropping the transaction discards it
+        let mut tx = vec.transaction();
+        tx.remove(a);
+        tx.insert(5);
+        drop(tx);
+        assert_eq!(vec.count(), 2);
+
+        // A single invalid edit discards the whole transaction
+        let mut tx = vec.transaction();
+        tx.update(a, 10);
+        tx.remove(b);
+        tx.remove(b);
+        assert_eq!(tx.len(), 3);
+        assert_eq!(tx.commit(), None);
+        assert_eq!(vec[a], 0);
+        assert_eq!(vec[b], 1);
+
+        let mut tx = vec.transaction();
+        tx.update(a, 10);
+        tx.remove(b);
+        assert_eq!(tx.insert(2), 0);
+        assert_eq!(tx.insert(3), 1);
+        assert_eq!(tx.get(b), Some(&1));
+        let ids = tx.commit().unwrap();
+        assert_eq!(vec[a], 10);
+        assert_eq!(vec.get(b), None);
+        assert_eq!(vec[ids[0]], 2);
+        assert_eq!(vec[ids[1]], 3);
+        assert_eq!(vec.count(), 3);
+    }
+    // Test undoing and redoing edits
+    #[test]
+    pub fn history_test() {
+        let mut vec = HistoryOrderedVec::<u32>::new();
+        let a = vec.push_shove(0);
+        let b = vec.push_shove(1);
+        assert_eq!(vec.overwrite(a, 10), Some(0));
+        assert_eq!(vec.remove(b), Some(1));
+        assert_eq!(vec.undo_count(), 4);
+
+        // Undoing the remove gives back the same ID
+        assert!(vec.undo());
+        assert_eq!(vec.get(b), Some(&1));
+        assert!(vec.undo());
+        assert_eq!(vec[a], 0);
+        assert!(vec.redo());
+        assert_eq!(vec[a], 10);
+        assert_eq!(vec.redo_count(), 1);
+
+        // Undo everything, then redo everything
+        while vec.undo() {}
+        assert_eq!(vec.count(), 0);
+        while vec.redo() {}
+        assert_eq!(vec.count(), 1);
+        assert_eq!(vec[a], 10);
+        assert_eq!(vec.get(b), None);
+
+        // A new edit forgets what was undone
+        vec.undo();
+        vec.push_shove(2);
+        assert_eq!(vec.redo_count(), 0);
+        assert!(!vec.redo());
+    }
+    // Test rebuilding a shareable ordered vec from its IDs and elements
+    #[test]
+    pub fn shareable_collect_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..4)
+            .map(|x| {
+                let id = vec.get_next_id_increment();
+                vec.insert(id, x).unwrap();
+                id
+            })
+            .collect::<Vec<_>>();
+        vec.remove(ids[1]);
+        let id = vec.get_next_id_increment();
+        vec.insert(id, 10).unwrap();
+        vec.remove(ids[2]);
+
+        // Rebuild it in reverse order, every element keeps its ID
+        let saved = vec.iter().map(|(id, x)| (id, *x)).collect::<Vec<_>>();
+        let rebuilt = saved
+            .iter()
+            .rev()
+            .copied()
+            .collect::<ShareableOrderedVec<u32>>();
+        assert_eq!(rebuilt.count(), 3);
+        assert_eq!(rebuilt[ids[0]], 0);
+        assert_eq!(rebuilt[id], 10);
+        assert_eq!(rebuilt[ids[3]], 3);
+        assert_eq!(rebuilt.get(ids[2]), None);
+
+        let mut extended = ShareableOrderedVec::<u32>::default();
+        extended.extend(saved.iter().copied().take(1));
+        extended.extend(saved.iter().copied().skip(1));
+        assert_eq!(extended.into_iter().collect::<Vec<_>>(), saved);
+        let mut sum = 0;
+        for (_, x) in rebuilt {
+            sum += x;
+        }
+        assert_eq!(sum, 13);
+    }
+    // Test getting multiple mutable references at once
+    #[test]
+    pub fn get_disjoint_mut_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..3)
+            .map(|x| {
+                let id = vec.get_next_id_increment();
+                vec.insert(id, x * 10).unwrap();
+                id
+            })
+            .collect::<Vec<_>>();
+        let [a, c] = vec.get_disjoint_mut([ids[0], ids[2]]).unwrap();
+        std::mem::swap(a, c);
+        assert_eq!(vec[ids[0]], 20);
+        assert_eq!(vec[ids[2]], 0);
+
+        // The same slot twice, or a stale ID, are rejected
+        assert!(vec.get_disjoint_mut([ids[1], ids[1]]).is_none());
+        vec.remove(ids[1]);
+        assert!(vec.get_disjoint_mut([ids[0], ids[1]]).is_none());
+        let id = vec.get_next_id_increment();
+        vec.insert(id, 5).unwrap();
+        assert!(vec.get_disjoint_mut([ids[1], ids[2]]).is_none());
+        assert_eq!(vec.get_disjoint_mut([id]).map(|[x]| *x), Some(5));
+    }
+    // Test retaining elements of a shareable ordered vec
+    #[test]
+    pub fn shareable_retain_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..6)
+            .map(|x| {
+                let id = vec.get_next_id_increment();
+                vec.insert(id, x).unwrap();
+                id
+            })
+            .collect::<Vec<_>>();
+        vec.remove(ids[1]);
+        vec.get_next_id_increment();
+        vec.retain(|id, x| id != ids[0] && x % 2 == 0);
+        assert_eq!(vec.iter_elements().copied().collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(vec.count_invalid(), 4);
+        assert_eq!(vec.count(), 2);
+
+        // The removed slots get reserved next, and a reserved slot is never handed out twice
+        let next = vec.get_next_id();
+        let id = vec.get_next_id_increment();
+        assert_eq!(next, id);
+        assert_ne!(vec.get_next_id(), id);
+        vec.insert(id, 10).unwrap();
+        assert_eq!(vec.count(), 3);
+    }
+    // Test preallocating a shareable ordered vec before reserving IDs
+    #[test]
+    pub fn shareable_capacity_test() {
+        let mut vec = ShareableOrderedVec::<u32>::with_capacity(8);
+        assert!(vec.capacity() >= 8);
+        let ids = (0..8)
+            .map(|_| vec.get_next_id_increment())
+            .collect::<Vec<_>>();
+        let capacity = vec.capacity();
+        for (x, id) in ids.into_iter().enumerate().rev() {
+            vec.insert(id, x as u32).unwrap();
+        }
+        assert_eq!(vec.capacity(), capacity);
+        assert_eq!(vec.count(), 8);
+
+        // Pending reservations are accounted for
+        let ids = (0..4)
+            .map(|_| vec.get_next_id_increment())
+            .collect::<Vec<_>>();
+        vec.reserve(4);
+        assert!(vec.capacity() >= 16);
+        let capacity = vec.capacity();
+        for id in ids {
+            vec.insert(id, 0).unwrap();
+        }
+        assert_eq!(vec.capacity(), capacity);
+    }
+    // Test dropping a lazy unversioned drain mid-way
+    #[test]
+    pub fn unversioned_lazy_drain_test() {
+        let mut vec = UnversionnedOrderedVec::<i32>::from_valids((0..6).collect());
+        let mut removed = vec.my_drain(|_index, val| val % 2 == 0);
+        assert_eq!(removed.next(), Some((0, 0)));
+        assert_eq!(removed.next(), Some((2, 2)));
+        drop(removed);
         assert_eq!(vec.count(), 4);
+        assert_eq!(vec.iter_invalid().copied().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(vec.get(4), Some(&4));
+        let rest = vec.my_drain(|_, _| true).collect::<Vec<_>>();
+        assert_eq!(rest, vec![(1, 1), (3, 3), (4, 4), (5, 5)]);
+        assert_eq!(vec.count_invalid(), 6);
+    }
+    // Test dropping a lazy shareable drain mid-way
+    #[test]
+    pub fn shareable_lazy_drain_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..6)
+            .map(|x| {
+                let id = vec.get_next_id_increment();
+                vec.insert(id, x).unwrap();
+                id
+            })
+            .collect::<Vec<_>>();
+        let mut removed = vec.my_drain(|_, x| x % 2 == 1);
+        assert_eq!(removed.next(), Some((ids[1], 1)));
+        drop(removed);
+        assert_eq!(vec.count(), 5);
+        assert_eq!(vec.iter_invalid().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(vec.get(ids[3]), Some(&3));
+
+        // The freed slot gets re-used exactly once
+        let id = vec.get_next_id_increment();
+        assert_eq!(vec.codec().decode(id).0, 1);
+        vec.insert(id, 10).unwrap();
+        assert_eq!(vec.count_invalid(), 0);
+        assert_eq!(vec.my_drain(|_, x| x % 2 == 1).count(), 2);
+        assert_eq!(vec.count_invalid(), 2);
+    }
+    // Test getting the future IDs of the null elements
+    #[test]
+    pub fn vacant_ids_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let ids = (0..4).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.iter_vacant_ids().count(), 0);
+        vec.remove(ids[1]);
+        vec.remove(ids[3]);
+        let mut vacant = vec.iter_vacant_ids().collect::<Vec<_>>();
+        assert_eq!(vacant, vec![Id::new(1, 1), Id::new(3, 1)]);
+
+        // Every vacant ID gets handed out by push_shove
+        let mut pushed = vec![vec.push_shove(10), vec.push_shove(11)];
+        vacant.sort_by_key(|id| id.to_u64());
+        pushed.sort_by_key(|id| id.to_u64());
+        assert_eq!(vacant, pushed);
+        assert_eq!(vec.iter_vacant_ids().count(), 0);
+    }
+    // Test updating elements in place versus giving them a new identity
+    #[test]
+    pub fn replace_set_test() {
+        let mut vec = OrderedVec::<&str>::default();
+        let id = vec.push_shove("a");
+        assert_eq!(vec.replace(id, "b"), Some("a"));
+        assert_eq!(vec.get(id), Some(&"b"));
+
+        vec.set_tag(id, 3);
+        let new = vec.set(id, "c").unwrap();
+        assert_ne!(new, id);
+        assert_eq!(vec.get(id), None);
+        assert_eq!(vec.get(new), Some(&"c"));
+        assert_eq!(vec.tags(new), Some(0));
+        assert_eq!(vec.count(), 1);
+
+        // Stale IDs do nothing
+        assert_eq!(vec.replace(id, "d"), None);
+        assert_eq!(vec.set(id, "d"), None);
+        vec.remove(new);
+        assert_eq!(vec.set(new, "d"), None);
+        assert_eq!(vec.count(), 0);
+    }
+    // Test consuming an ordered vec into dense Vecs
+    #[test]
+    pub fn compact_into_vec_test() {
+        let mut vec = OrderedVec::<String>::default();
+        let ids = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(|x| vec.push_shove(x.to_string()))
+            .collect::<Vec<_>>();
+        vec.remove(ids[1]);
+        let (values, packed) = vec.compact_into_vec();
+        assert_eq!(values, vec!["a", "c", "d"]);
+        assert_eq!(packed, vec![ids[0], ids[2], ids[3]]);
+    }
+    // Test upserting elements using IDs that do not exist yet
+    #[test]
+    pub fn get_mut_or_insert_with_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let id = Id::new(3, 2);
+        *vec.get_mut_or_insert_with(id, || 5).unwrap() += 1;
+        assert_eq!(vec.get(id), Some(&6));
+        assert_eq!(vec.count(), 1);
+        assert_eq!(vec.count_invalid(), 3);
+
+        // Live elements are not overwritten
+        *vec.get_mut_or_insert_with(id, || 0).unwrap() += 1;
+        assert_eq!(vec.get(id), Some(&7));
+        assert!(vec.get_mut_or_insert_with(Id::new(3, 1), || 0).is_none());
+
+        // The gap gets re-used as usual
+        let other = vec.push_shove(1);
+        assert!(vec.get(other).is_some());
+        assert_eq!(vec.count_invalid(), 2);
+    }
+    // Test rejecting fabricated IDs when strictly inserting
+    #[test]
+    pub fn shareable_insert_strict_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let a = vec.get_next_id_increment();
+        let b = vec.get_next_id_increment();
+        assert_eq!(vec.insert_strict(b, 1), Ok(None));
+        assert_eq!(vec.insert_strict(a, 0), Ok(None));
+
+        // Indices past the reservations are rejected
+        let fabricated = vec.codec().encode(5, 0);
+        assert_eq!(
+            vec.insert_strict(fabricated, 2),
+            Err(Unreserved(fabricated))
+        );
+        assert_eq!(vec.count(), 2);
+
+        // So are stale versions of existing slots
+        vec.remove(a);
+        assert_eq!(vec.insert_strict(a, 3), Err(Unreserved(a)));
+        let c = vec.get_next_id_increment();
+        assert_eq!(vec.insert_strict(c, 3), Ok(None));
+        assert_eq!(vec.get(c), Some(&3));
+    }
+    // Test the checked constructors for indices that do not fit
+    #[test]
+    pub fn checked_index_test() {
+        use crate::utils::{IdCodec, IndexPair};
+        assert_eq!(Id::try_new(7, 2), Some(Id::new(7, 2)));
+        assert_eq!(
+            IndexPair::try_new(u32::MAX as usize, 1),
+            Some(IndexPair::new(u32::MAX as usize, 1))
+        );
+        assert_eq!(IdCodec::DEFAULT.try_encode(3, 1), Some(Id::new(3, 1)));
+        if usize::BITS > 32 {
+            let huge = u32::MAX as usize + 1;
+            assert_eq!(IndexPair::try_new(huge, 0), None);
+            assert_eq!(Id::try_new(huge, 0), None);
+            assert_eq!(IdCodec::DEFAULT.try_encode(huge, 0), None);
+
+            // The wide codec can address it
+            let id = IdCodec::LARGE.try_encode(huge, 5).unwrap();
+            assert_eq!(IdCodec::LARGE.decode(id), (huge, 5));
+        }
+    }
+    // Test the invariant checker on healthy collections
+    #[test]
+    pub fn validate_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let ids = (0..8).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[2]);
+        vec.remove(ids[5]);
+        assert_eq!(vec.validate(), Ok(()));
+        vec.push_shove(10);
+        assert_eq!(vec.validate(), Ok(()));
+
+        let mut dense = DenseOrderedVec::<u32>::default();
+        let ids = (0..8).map(|x| dense.push_shove(x)).collect::<Vec<_>>();
+        dense.remove(ids[0]);
+        dense.remove(ids[4]);
+        assert_eq!(dense.validate(), Ok(()));
+
+        let mut hop = HopOrderedVec::<u32>::default();
+        let ids = (0..8).map(|x| hop.push_shove(x)).collect::<Vec<_>>();
+        hop.remove(ids[3]);
+        assert_eq!(hop.validate(), Ok(()));
+
+        let mut tracked = TrackedOrderedVec::<u32>::default();
+        let id = tracked.push_shove(0);
+        tracked.push_shove(1);
+        tracked.remove(id);
+        assert_eq!(tracked.validate(), Ok(()));
+
+        let mut paged = PagedOrderedVec::<u32>::default();
+        let ids = (0..2000).map(|x| paged.push_shove(x)).collect::<Vec<_>>();
+        paged.remove(ids[1500]);
+        assert_eq!(paged.validate(), Ok(()));
+
+        let mut cow = CowOrderedVec::<u32>::default();
+        let ids = (0..100).map(|x| cow.push_shove(x)).collect::<Vec<_>>();
+        cow.remove(ids[70]);
+        assert_eq!(cow.clone().validate(), Ok(()));
+
+        let mut fixed = FixedOrderedVec::<u32, 4>::default();
+        let id = fixed.push_shove(0).unwrap();
+        fixed.push_shove(1).unwrap();
+        fixed.remove(id);
+        assert_eq!(fixed.validate(), Ok(()));
+
+        let mut keyed = KeyedOrderedVec::<&str, u32>::default();
+        keyed.insert("a", 0);
+        assert_eq!(keyed.validate(), Ok(()));
+
+        // Slots that were reserved but not inserted yet are fine
+        let mut shareable = ShareableOrderedVec::<u32>::default();
+        let _a = shareable.get_next_id_increment();
+        let b = shareable.get_next_id_increment();
+        shareable.insert(b, 1).unwrap();
+        assert_eq!(shareable.validate(), Ok(()));
+    }
+    // Test the invariant checker on corrupted collections
+    #[test]
+    pub fn validate_corrupted_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let ids = (0..4).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[1]);
+        vec.slots.missing.push(1);
+        assert_eq!(
+            vec.validate(),
+            Err(InvariantError::DuplicateFreeSlot { index: 1 })
+        );
+        vec.slots.missing = vec![2];
+        assert_eq!(
+            vec.validate(),
+            Err(InvariantError::FreeSlotOccupied { index: 2 })
+        );
+        vec.slots.missing = vec![9];
+        assert_eq!(
+            vec.validate(),
+            Err(InvariantError::FreeSlotOutOfBounds { index: 9, len: 4 })
+        );
+        vec.slots.missing.clear();
+        assert_eq!(
+            vec.validate(),
+            Err(InvariantError::UntrackedFreeSlot { index: 1 })
+        );
+
+        let mut dense = DenseOrderedVec::<u32>::default();
+        dense.push_shove(0);
+        dense.push_shove(1);
+        dense.owners.swap(0, 1);
+        assert_eq!(
+            dense.validate(),
+            Err(InvariantError::BrokenBacklink { index: 0 })
+        );
+
+        let mut shareable = ShareableOrderedVec::<u32>::default();
+        let id = shareable.get_next_id_increment();
+        shareable.insert(id, 0).unwrap();
+        shareable.reservations = crate::shareable_ordered_vec::Reservations::new();
+        assert_eq!(
+            shareable.validate(),
+            Err(InvariantError::LengthMismatch {
+                what: "length counter",
+                expected: 1,
+                found: 0
+            })
+        );
+    }
+    // Test catching a corrupted free list at the mutation that uses it
+    #[cfg(feature = "debug-validate")]
+    #[test]
+    #[should_panic(expected = "push_shove broke an invariant")]
+    pub fn debug_validate_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let id = vec.push_shove(0);
+        vec.push_shove(1);
+        vec.remove(id);
+        vec.slots.missing.push(0);
+        vec.push_shove(2);
+    }
+    // Test that adding a brand new slot gets checked too, not only re-using a free one
+    #[cfg(feature = "debug-validate")]
+    #[test]
+    #[should_panic(expected = "push_shove broke an invariant")]
+    pub fn debug_validate_append_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        vec.push_shove(0);
+        vec.slots.vec[0].0 = None;
+        vec.push_shove(1);
+    }
+    // Test growing an ordered vec incrementally
+    #[test]
+    pub fn incremental_test() {
+        let mut vec = IncrementalOrderedVec::<u32>::with_migration_step(2);
+        let mut ids = (0..64).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        while vec.migrate_step() {}
+        assert!(!vec.is_migrating());
+
+        // Filling up the buffer starts a migration instead of a big copy
+        let capacity = vec.slots.vec.current.capacity();
+        while vec.slots.vec.current.len() < capacity {
+            ids.push(vec.push_shove(ids.len() as u32));
+        }
+        ids.push(vec.push_shove(ids.len() as u32));
+        assert!(vec.is_migrating());
+        let tail = vec.slots.vec.tail.capacity();
+        let current = vec.slots.vec.current.capacity();
+        vec.remove(ids[3]);
+        ids[3] = vec.push_shove(3);
+        for (x, id) in ids.iter().enumerate() {
+            assert_eq!(vec.get(*id), Some(&(x as u32)));
+        }
+        assert_eq!(vec.validate(), Ok(()));
+
+        // Nothing reallocates while migrating
+        while vec.is_migrating() {
+            assert_eq!(vec.slots.vec.tail.capacity(), tail);
+            assert_eq!(vec.slots.vec.current.capacity(), current);
+            ids.push(vec.push_shove(ids.len() as u32));
+        }
+        assert_eq!(vec.slots.vec.current.capacity(), current);
+        assert_eq!(vec.count(), ids.len());
+        let values = vec.iter().map(|(_, x)| *x).collect::<Vec<_>>();
+        assert_eq!(values, (0..ids.len() as u32).collect::<Vec<_>>());
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    // Test growing the slots using custom growth policies
+    #[test]
+    pub fn growth_policy_test() {
+        use crate::utils::{Increment, OneAndAHalf};
+        let mut vec = OrderedVec::<u32>::with_growth_policy(Increment(10));
+        vec.push_shove(0);
+        assert_eq!(vec.slots.vec.capacity(), 10);
+        for x in 1..11 {
+            vec.push_shove(x);
+        }
+        assert_eq!(vec.slots.vec.capacity(), 20);
+
+        let mut vec = UnversionnedOrderedVec::<u32>::with_growth_policy(OneAndAHalf);
+        for x in 0..5 {
+            vec.push_shove(x);
+        }
+        assert_eq!(vec.slots.vec.capacity(), 6);
+
+        // Closures work too, and the policy survives cloning
+        let mut vec = OrderedVec::<u32>::with_growth_policy(|_, needed: usize| {
+            needed.next_power_of_two() * 4
+        });
+        vec.push_shove(0);
+        let mut clone = vec.clone();
+        for x in 0..4 {
+            clone.push_shove(x);
+        }
+        assert_eq!(clone.slots.vec.capacity(), 8);
+
+        // The shareable vector grows once for the whole gap
+        let mut vec = ShareableOrderedVec::<u32>::with_growth_policy(Increment(100));
+        let id = vec.codec().encode(40, 0);
+        vec.insert(id, 0).unwrap();
+        assert_eq!(vec.capacity(), 100);
+    }
+    // Test the slots that fill the gaps of a shareable ordered vec
+    #[test]
+    pub fn shareable_gap_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..3)
+            .map(|_| vec.get_next_id_increment())
+            .collect::<Vec<_>>();
+        vec.insert(ids[2], 2).unwrap();
+        assert_eq!(vec.validate(), Ok(()));
+
+        // The gap slots start at the first version, just like brand new ones
+        assert_eq!(vec.insert(ids[0], 0), Ok(None));
+        assert_eq!(vec.get(ids[0]), Some(&0));
+        assert_eq!(vec.codec().decode(ids[0]).1, 0);
+        assert_eq!(vec.metrics().reuses, 0);
+        assert_eq!(
+            std::mem::size_of::<(Option<u32>, u32)>(),
+            std::mem::size_of_val(&vec.slots.vec[0])
+        );
+    }
+    // Test converting ordered vecs from and into maps and pairs
+    #[test]
+    pub fn conversions_test() {
+        let mut vec = OrderedVec::<&str>::default();
+        let ids = ["a", "b", "c"]
+            .into_iter()
+            .map(|x| vec.push_shove(x))
+            .collect::<Vec<_>>();
+        vec.remove(ids[1]);
+        let b = vec.push_shove("d");
+
+        let pairs: Vec<(u64, &str)> = vec.clone().into();
+        assert_eq!(
+            pairs,
+            vec![
+                (ids[0].to_u64(), "a"),
+                (b.to_u64(), "d"),
+                (ids[2].to_u64(), "c")
+            ]
+        );
+        let map: HashMap<u64, &str> = vec.into();
+        assert_eq!(map.len(), 3);
+
+        // The indices and versions are honored
+        let vec = OrderedVec::from(map);
+        assert_eq!(vec.get(b), Some(&"d"));
+        assert_eq!(vec.get(ids[1]), None);
+        let vec = OrderedVec::from(vec![(Id::new(4, 2).to_u64(), "e")]);
+        assert_eq!(vec.get(Id::new(4, 2)), Some(&"e"));
+        assert_eq!(vec.count_invalid(), 4);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    // Test iterating over references of a shareable ordered vec
+    #[test]
+    pub fn shareable_ref_into_iter_test() {
+        let mut vec = (0..4)
+            .map(|x| (Id::new(x, 0), x as u32))
+            .collect::<ShareableOrderedVec<u32>>();
+        vec.remove(Id::new(1, 0));
+        for (_, x) in &mut vec {
+            *x *= 10;
+        }
+        let mut seen = Vec::new();
+        for (id, x) in &vec {
+            seen.push((id, *x));
+        }
+        assert_eq!(
+            seen,
+            vec![(Id::new(0, 0), 0), (Id::new(2, 0), 20), (Id::new(3, 0), 30)]
+        );
+    }
+    // Test writing code that works with any ordered collection
+    #[test]
+    pub fn ordered_collection_test() {
+        fn churn<C: OrderedCollection<Elem = u32>>(mut collection: C) -> Vec<u32> {
+            let keys = (0..5).map(|x| collection.push(x)).collect::<Vec<_>>();
+            collection.remove(keys[1]);
+            *collection.get_mut(keys[2]).unwrap() += 10;
+            assert_eq!(collection.get(keys[1]), None);
+            collection.push(7);
+            assert_eq!(collection.count(), 5);
+            collection.iter().map(|(_, x)| *x).collect()
+        }
+        let expected = vec![0, 7, 12, 3, 4];
+        assert_eq!(churn(OrderedVec::default()), expected);
+        assert_eq!(churn(UnversionnedOrderedVec::default()), expected);
+        assert_eq!(churn(ShareableOrderedVec::default()), expected);
+    }
+    // Test keeping a shadow copy in sync with a mirror on another thread
+    #[test]
+    pub fn mirror_test() {
+        let mut mirror = Mirror::<String>::new();
+        let a = mirror.push_shove("a".to_string());
+        let mut shadow = mirror.shadow();
+        assert_eq!(mirror.pending(), 0);
+
+        let b = mirror.push_shove("b".to_string());
+        mirror.remove(a);
+        let c = mirror.push_shove("c".to_string());
+        mirror.update(b, "B".to_string());
+        assert_eq!(mirror.pending(), 4);
+        let patch = mirror.take_patch();
+        let shadow = std::thread::spawn(move || {
+            assert!(shadow.apply_patch(patch));
+            shadow
+        })
+        .join()
+        .unwrap();
+        assert_eq!(shadow.get(a), None);
+        assert_eq!(shadow.get(b).map(String::as_str), Some("B"));
+        assert_eq!(shadow.get(c).map(String::as_str), Some("c"));
+        assert_eq!(
+            shadow.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            mirror.iter().map(|(id, _)| id).collect::<Vec<_>>()
+        );
+
+        // Syncing directly works as well
+        let mut shadow = shadow;
+        mirror.remove(c);
+        assert!(mirror.sync(&mut shadow));
+        assert_eq!(shadow.count(), 1);
+        assert_eq!(mirror.pending(), 0);
+    }
+    // Test serializing recorded changes and replaying them onto a fresh ordered vec
+    #[test]
+    pub fn replay_ops_test() {
+        let mut mirror = Mirror::<u32>::new();
+        let ids = (0..4).map(|x| mirror.push_shove(x)).collect::<Vec<_>>();
+        mirror.remove(ids[1]);
+        mirror.update(ids[2], 20);
+        mirror.push_shove(5);
+        let mut bytes = Vec::new();
+        mirror
+            .take_patch()
+            .encode(&mut bytes, &LittleEndian)
+            .unwrap();
+
+        let patch = Patch::<u32>::decode(&mut bytes.as_slice(), &LittleEndian).unwrap();
+        assert_eq!(patch.changes.len(), 7);
+        let mut replayed = OrderedVec::<u32>::default();
+        assert!(replayed.apply_ops(patch.changes));
+        assert_eq!(
+            replayed.iter().collect::<Vec<_>>(),
+            mirror.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(replayed.validate(), Ok(()));
+
+        // Garbage gets rejected
+        bytes[0] = b'X';
+        assert!(Patch::<u32>::decode(&mut bytes.as_slice(), &LittleEndian).is_err());
+    }
+    #[test]
+    pub fn stable_defragment_test() {
+        let mut vec = StableOrderedVec::<u32>::new();
+        let ids = (0..6).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[0]);
+        vec.remove(ids[2]);
+        vec.remove(ids[3]);
+        assert_eq!(vec.count_holes(), 3);
+        assert_eq!(vec.metrics().largest_free_run, 2);
+
+        // Every outstanding ID stays valid, and the values keep their order
+        assert_eq!(vec.defragment(), 3);
+        assert_eq!(vec.count_holes(), 0);
+        assert_eq!(vec.values.len(), 3);
+        assert_eq!(vec[ids[1]], 1);
+        assert_eq!(vec[ids[4]], 4);
+        assert_eq!(vec[ids[5]], 5);
+        assert_eq!(vec.get(ids[2]), None);
+        assert_eq!(
+            vec.iter().map(|(_, x)| *x).collect::<Vec<_>>(),
+            vec![1, 4, 5]
+        );
+        assert_eq!(vec.validate(), Ok(()));
+
+        // The removed IDs still don't alias the new elements
+        let id = vec.push_shove(10);
+        assert_eq!(vec.get(ids[3]), None);
+        assert_eq!(vec[id], 10);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn shareable_free_list_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..64)
+            .map(|x| {
+                let id = vec.get_next_id_increment();
+                vec.insert(id, x).unwrap();
+                id
+            })
+            .collect::<Vec<_>>();
+        for id in ids.iter().step_by(2) {
+            vec.remove(*id);
+        }
+
+        // Many threads reserving at once still get exactly the free slots, and then brand new ones
+        let arc = Arc::new(vec);
+        let thread_join_handles = (0..8)
+            .map(|_| {
+                let arc = arc.clone();
+                std::thread::spawn(move || {
+                    (0..8)
+                        .map(|_| arc.get_next_id_increment())
+                        .collect::<Vec<Id>>()
+                })
+            })
+            .collect::<Vec<JoinHandle<Vec<Id>>>>();
+        let mut reserved = thread_join_handles
+            .into_iter()
+            .flat_map(|x| x.join().unwrap())
+            .collect::<Vec<Id>>();
+        let mut vec = Arc::try_unwrap(arc).unwrap();
+        reserved.sort_unstable_by_key(|id| vec.codec().decode(*id).0);
+        let indices = reserved
+            .iter()
+            .map(|id| vec.codec().decode(*id).0)
+            .collect::<Vec<_>>();
+        let expected = (0..64).step_by(2).chain(64..96).collect::<Vec<_>>();
+        assert_eq!(indices, expected);
+        for (x, id) in reserved.into_iter().enumerate() {
+            assert_eq!(vec.insert(id, x as u32), Ok(None));
+        }
+        assert_eq!(vec.count(), 96);
+        assert_eq!(vec.count_invalid(), 0);
+        assert_eq!(vec.validate(), Ok(()));
+
+        // Filling a free slot with an explicit ID takes it out of the free list
+        vec.remove(ids[1]);
+        vec.remove(ids[3]);
+        vec.insert(Id::new(3, 1), 3).unwrap();
+        assert_eq!(vec.validate(), Ok(()));
+        assert_eq!(vec.get_next_id_increment(), Id::new(1, 1));
+        assert_eq!(vec.get_next_id_increment(), Id::new(96, 0));
+    }
+    #[test]
+    pub fn shareable_packed_reservations_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let id = vec.get_next_id_increment();
+        vec.insert(id, 0).unwrap();
+        vec.remove(id);
+
+        // The free slot and the brand new slots get handed out by the same atomic, so nothing overlaps
+        let arc = Arc::new(vec);
+        let thread_join_handles = (0..8)
+            .map(|_| {
+                let arc = arc.clone();
+                std::thread::spawn(move || {
+                    (0..100)
+                        .map(|_| arc.codec().decode(arc.get_next_id_increment()).0)
+                        .collect::<Vec<usize>>()
+                })
+            })
+            .collect::<Vec<JoinHandle<Vec<usize>>>>();
+        let mut indices = thread_join_handles
+            .into_iter()
+            .flat_map(|x| x.join().unwrap())
+            .collect::<Vec<usize>>();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..800).collect::<Vec<_>>());
+        let vec = Arc::try_unwrap(arc).unwrap();
+        assert_eq!(vec.get_next_id(), Id::new(800, 0));
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn shareable_reserved_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..3)
+            .map(|x| {
+                let id = vec.get_next_id_increment();
+                vec.insert(id, x).unwrap();
+                id
+            })
+            .collect::<Vec<_>>();
+        vec.remove(ids[1]);
+
+        // One re-used slot, and two brand new ones
+        let reused = vec.get_next_id_increment();
+        let first = vec.get_next_id_increment();
+        let last = vec.get_next_id_increment();
+        assert_eq!(vec.count_reserved(), 3);
+        assert_eq!(
+            vec.iter_reserved().collect::<Vec<_>>(),
+            vec![reused, first, last]
+        );
+
+        // The element of the last reservation arrives first, so the slot before it stays reserved
+        vec.insert(last, 5).unwrap();
+        assert_eq!(vec.count(), 3);
+        assert_eq!(vec.iter().count(), vec.count());
+        assert_eq!(vec.iter_reserved().collect::<Vec<_>>(), vec![reused, first]);
+        assert_eq!(vec.count_invalid(), 1);
+        assert_eq!(vec.validate(), Ok(()));
+
+        vec.insert(reused, 1).unwrap();
+        vec.insert(first, 4).unwrap();
+        assert_eq!(vec.count_reserved(), 0);
+        assert_eq!(vec.count(), 5);
         assert_eq!(vec.count_invalid(), 0);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn shareable_retain_keeps_reservations_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let ids = (0..4)
+            .map(|x| {
+                let id = vec.get_next_id_increment();
+                vec.insert(id, x).unwrap();
+                id
+            })
+            .collect::<Vec<_>>();
+        vec.remove(ids[1]);
+
+        // A worker reserved the free slot before the owner thread retained
+        let reserved = vec.get_next_id_increment();
+        vec.retain(|_, x| *x != 2);
+        let next = vec.get_next_id_increment();
+        assert_ne!(reserved, next);
+        assert_eq!(vec.codec().decode(next).0, 2);
+        assert_eq!(vec.count_reserved(), 2);
+        vec.insert(reserved, 10).unwrap();
+        vec.insert(next, 20).unwrap();
+        assert_eq!(vec.count(), 4);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn cow_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = CowOrderedVec::<u32>::with_codec(IdCodec::LARGE);
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        let copy = vec.clone();
+        vec.remove(ids[1]);
+        let id = vec.push_shove(10);
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(copy[ids[1]], 1);
+        assert_eq!(vec.validate(), Ok(()));
+
+        // The free slots get re-used in the order of the policy
+        let mut vec = CowOrderedVec::<u32>::with_reuse_policy(ReusePolicy::Fifo);
+        let ids = (0..4).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[2]);
+        vec.remove(ids[0]);
+        assert_eq!(vec.push_shove(5).index(), 2);
+        assert_eq!(vec.metrics().reuses, 1);
+    }
+    #[test]
+    pub fn fixed_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = FixedOrderedVec::<u32, 4>::with_codec(IdCodec::LARGE);
+        let ids = (0..3)
+            .map(|x| vec.push_shove(x).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        vec.remove(ids[1]);
+        let id = vec.push_shove(10).unwrap();
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(vec.get(ids[1]), None);
+        assert_eq!(vec.validate(), Ok(()));
+
+        // The free slots get re-used in the order of the policy
+        let mut vec = FixedOrderedVec::<u32, 4>::with_reuse_policy(ReusePolicy::Fifo);
+        let ids = (0..4)
+            .map(|x| vec.push_shove(x).unwrap())
+            .collect::<Vec<_>>();
+        vec.remove(ids[2]);
+        vec.remove(ids[0]);
+        assert_eq!(vec.push_shove(5).unwrap().index(), 2);
+        assert_eq!(vec.metrics().reuses, 1);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn paged_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = PagedOrderedVec::<u32>::with_codec(IdCodec::LARGE);
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        vec.remove(ids[1]);
+        let id = vec.push_shove(10);
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(vec.get(ids[1]), None);
+        assert_eq!(vec.validate(), Ok(()));
+
+        // The free slots get re-used in the order of the policy
+        let mut vec = PagedOrderedVec::<u32>::with_reuse_policy(ReusePolicy::Fifo);
+        let ids = (0..4).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[2]);
+        vec.remove(ids[0]);
+        assert_eq!(vec.push_shove(5).index(), 2);
+        assert_eq!(vec.metrics().reuses, 1);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn incremental_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = IncrementalOrderedVec::<u32>::with_codec(IdCodec::LARGE);
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        vec.remove(ids[1]);
+        let id = vec.push_shove(10);
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(vec.get(ids[1]), None);
+        assert_eq!(vec.validate(), Ok(()));
+
+        // The free slots get re-used in the order of the policy
+        let mut vec = IncrementalOrderedVec::<u32>::with_reuse_policy(ReusePolicy::Fifo);
+        let ids = (0..4).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.remove(ids[2]);
+        vec.remove(ids[0]);
+        assert_eq!(vec.push_shove(5).index(), 2);
+        assert_eq!(vec.metrics().reuses, 1);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn tags_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = OrderedVec::<u32>::with_codec(IdCodec::LARGE);
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert!(vec.set_tag(ids[2], 4));
+        assert_eq!(vec.tags(ids[2]), Some(1 << 4));
+        assert_eq!(vec.tags(ids[0]), Some(0));
+        assert_eq!(
+            vec.iter_filtered(1 << 4)
+                .map(|(_, x)| *x)
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert!(vec.unset_tag(ids[2], 4));
+        assert_eq!(vec.iter_filtered(1 << 4).count(), 0);
+
+        // Stale IDs don't touch the tags of the element that re-used their slot
+        vec.remove(ids[1]);
+        let id = vec.push_shove(3);
+        assert!(!vec.set_tag(ids[1], 0));
+        assert_eq!(vec.tags(ids[1]), None);
+        assert_eq!(vec.tags(id), Some(0));
+        assert!(!vec.set_tag(Id::NULL, 0));
+    }
+    #[test]
+    pub fn shareable_invalid_insert_test() {
+        use crate::shareable::InvalidId;
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        assert_eq!(vec.insert(Id::NULL, 0), Err(InvalidId(Id::NULL)));
+        assert_eq!(vec.place(Id::NULL, 0), Err(InvalidId(Id::NULL)));
+
+        // Indices that don't fit inside the free list never resize the vector
+        let garbage = Id::new(u32::MAX as usize - 1, 0);
+        assert_eq!(vec.insert(garbage, 0), Err(InvalidId(garbage)));
+        assert_eq!(vec.place(garbage, 0), Err(InvalidId(garbage)));
+        assert_eq!(vec.metrics().bytes_allocated, 0);
+        let id = vec.get_next_id_increment();
+        assert_eq!(vec.insert(id, 1), Ok(None));
+        assert_eq!(vec.count(), 1);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn join_codec_test() {
+        use crate::utils::IdCodec;
+        let mut positions = OrderedVec::<i32>::with_codec(IdCodec::new(8));
+        let mut velocities = SecondaryOrderedVec::<i32>::for_primary(&positions);
+        let mut names = SecondaryOrderedVec::<&str>::for_primary(&positions);
+        let a = positions.push_shove(0);
+        let b = positions.push_shove(10);
+        let c = positions.push_shove(20);
+        velocities.insert(a, 1);
+        velocities.insert(c, 3);
+        names.insert(b, "b");
+        names.insert(c, "c");
+
+        // A stale ID in the secondary has the same index but a bigger raw value, so it must not hide the new element
+        positions.remove(a);
+        let d = positions.push_shove(100);
+        names.insert(d, "d");
+        let codec = positions.codec();
+        let joined = crate::join!(codec; positions.iter(), names.iter())
+            .map(|(id, _, name)| (id, *name))
+            .collect::<Vec<_>>();
+        assert_eq!(joined, vec![(d, "d"), (b, "b"), (c, "c")]);
+        let joined = crate::join!(codec; positions.iter(), velocities.iter(), names.iter())
+            .map(|(id, pos, vel, name)| (id, *pos, *vel, *name))
+            .collect::<Vec<_>>();
+        assert_eq!(joined, vec![(c, 20, 3, "c")]);
+    }
+    #[test]
+    pub fn pool_many_guards_test() {
+        let pool = Pool::<u32>::new();
+        let mut a = pool.acquire(1);
+        let b = pool.clone().acquire(2);
+        *a += 10;
+        assert_eq!(*a + *b, 13);
+        assert_eq!(pool.count(), 2);
+
+        // Acquired elements only show up in the pool once they are kept
+        assert!(pool.get(a.id()).is_none());
+        assert_eq!(pool.release(a.id()), None);
+        let kept = a.keep();
+        *pool.get_mut(kept).unwrap() += 1;
+        assert_eq!(*pool.get(kept).unwrap(), 12);
+        drop(b);
+        assert_eq!(pool.count(), 1);
+        assert_eq!(pool.release(kept), Some(12));
+        assert_eq!(pool.validate(), Ok(()));
+    }
+    #[test]
+    pub fn shared_pool_test() {
+        let pool = SharedPool::<u32>::new();
+        let handles = (0..4)
+            .map(|x| {
+                let pool = pool.clone();
+                std::thread::spawn(move || {
+                    let mut guard = pool.acquire(x);
+                    *guard += 1;
+                    if x % 2 == 0 {
+                        guard.keep();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(pool.count(), 2);
+        assert_eq!(pool.count_acquired(), 0);
+        let mut kept = pool.inner().iter_elements().copied().collect::<Vec<_>>();
+        kept.sort();
+        assert_eq!(kept, vec![1, 3]);
+
+        // The slots of the dropped guards get re-used
+        let a = pool.acquire(5);
+        let b = pool.acquire(6);
+        assert_eq!(pool.count_acquired(), 2);
+        assert_eq!(b.take(), 6);
+        let id = a.keep();
+        assert_eq!(pool.inner().get(id), Some(&5));
+        assert!(pool.metrics().bytes_allocated > 0);
+        assert_eq!(pool.release(id), Some(5));
+        assert_eq!(pool.count(), 2);
+        assert_eq!(pool.validate(), Ok(()));
+    }
+    #[test]
+    pub fn history_monotonic_version_test() {
+        let mut vec = HistoryOrderedVec::<u32>::new();
+        let a = vec.push_shove(0);
+        vec.overwrite(a, 1);
+        vec.remove(a);
+        let b = vec.push_shove(2);
+        assert_eq!(b.index(), a.index());
+
+        // B gets undone, then A comes back in the same slot, but it can't roll the version back to its old one
+        assert!(vec.undo());
+        assert!(vec.undo());
+        assert_eq!(vec.count(), 1);
+        assert_eq!(vec.get(a), None);
+        assert_eq!(vec.get(b), None);
+        let (restored, elem) = vec.iter().next().unwrap();
+        assert_eq!(*elem, 1);
+        assert!(restored.version() > b.version());
+
+        // The older edits of A follow it to its new ID
+        assert!(vec.undo());
+        assert_eq!(vec[restored], 0);
+        assert!(vec.redo());
+        assert_eq!(vec[restored], 1);
+        let c = vec.push_shove(3);
+        assert_ne!(c, a);
+        assert_ne!(c, b);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn small_full_test() {
+        let mut vec = SmallOrderedVec::<()>::new();
+        vec.slots
+            .vec
+            .resize_with(Id32::MAX_INDEX - 1, || (Some(()), 0));
+        let last = vec.push_shove(());
+        assert_eq!(last.index(), Id32::MAX_INDEX - 1);
+
+        // Every addressable slot is taken
+        assert_eq!(vec.get_next_id(), None);
+        assert_eq!(vec.try_push_shove(()), Err(()));
+        assert!(std::panic::catch_unwind(move || vec.push_shove(())).is_err());
+    }
+    #[test]
+    pub fn shareable_insert_strict_fabricated_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let a = vec.get_next_id_increment();
+        let b = vec.get_next_id_increment();
+        vec.insert_strict(a, 0).unwrap();
+        vec.insert_strict(b, 1).unwrap();
+
+        // The next version of an occupied slot would overwrite a live element
+        let (index, version) = vec.codec().decode(a);
+        let overwrite = vec.codec().encode(index, version + 1);
+        assert_eq!(vec.insert_strict(overwrite, 2), Err(Unreserved(overwrite)));
+        assert_eq!(vec.get(a), Some(&0));
+
+        // A free slot that nobody reserved yet is still on the free list
+        vec.remove(b);
+        let vacant = vec.get_next_id();
+        assert_eq!(vec.insert_strict(vacant, 3), Err(Unreserved(vacant)));
+        assert_eq!(vec.get_next_id_increment(), vacant);
+        assert_eq!(vec.insert_strict(vacant, 3), Ok(None));
+
+        // Indices past the end must have been reserved
+        let past = vec.codec().encode(2, 0);
+        assert_eq!(vec.insert_strict(past, 4), Err(Unreserved(past)));
+        assert_eq!(vec.get_next_id_increment(), past);
+        assert_eq!(vec.insert_strict(past, 4), Ok(None));
+        assert_eq!(vec.count(), 3);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn shareable_place_keeps_reservations_test() {
+        let mut vec = ShareableOrderedVec::<u32>::default();
+        let reserved = (0..2)
+            .map(|_| vec.get_next_id_increment())
+            .collect::<Vec<_>>();
+        vec.place(vec.codec().encode(4, 0), 4).unwrap();
+
+        // Only the gap past the reservations gets freed
+        let mut indices = reserved
+            .iter()
+            .map(|id| vec.codec().decode(*id).0)
+            .collect::<Vec<_>>();
+        indices.extend((0..3).map(|_| vec.codec().decode(vec.get_next_id_increment()).0));
+        indices.sort();
+        indices.dedup();
+        assert_eq!(indices.len(), 5);
+        assert!(!indices.contains(&4));
+        for (id, x) in reserved.into_iter().zip(0..) {
+            vec.insert(id, x).unwrap();
+        }
+        assert_eq!(vec.count(), 3);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn apply_ops_invalid_insert_test() {
+        use crate::utils::IdCodec;
+        let mut vec = OrderedVec::<u32>::with_codec(IdCodec::new(32).with_tag_bits(8));
+        let foreign =
+            OrderedVec::<u32>::with_codec(IdCodec::new(32).with_tag_bits(8)).get_next_id();
+        let far = vec.codec().encode(MAX_REPLAY_GAP + 1, 0);
+        assert!(!vec.apply_ops(vec![
+            Change::Insert(Id::NULL, 0),
+            Change::Insert(foreign, 1),
+            Change::Insert(far, 2),
+        ]));
+        assert_eq!(vec.count(), 0);
+        assert_eq!(vec.metrics().bytes_allocated, 0);
+
+        // Valid inserts still get applied, even with a small gap in front of them
+        let id = vec.codec().encode(3, 5);
+        assert!(vec.apply_ops(vec![Change::Insert(id, 7)]));
+        assert_eq!(vec[id], 7);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn dense_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = DenseOrderedVec::<u32>::with_codec(IdCodec::LARGE);
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        vec.remove(ids[1]);
+        let id = vec.push_shove(10);
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(vec.get(ids[1]), None);
+        assert_eq!(vec.get(Id::NULL), None);
+        assert!(vec.iter().any(|(x, val)| x == id && *val == 10));
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn hop_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = HopOrderedVec::<u32>::with_codec(IdCodec::LARGE);
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        vec.remove(ids[1]);
+        let id = vec.push_shove(10);
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(vec.get(ids[1]), None);
+        assert_eq!(vec.get(Id::NULL), None);
+        assert!(vec.iter().any(|(x, val)| x == id && *val == 10));
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn tracked_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = TrackedOrderedVec::<u32>::with_codec(IdCodec::new(32).with_tag_bits(8));
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        vec.remove(ids[1]);
+        let tick = vec.advance_tick();
+        let id = vec.push_shove(10);
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(vec.get(ids[1]), None);
+        assert_eq!(vec.get(Id::NULL), None);
+        assert_eq!(vec.ticks(id).unwrap().added, tick);
+
+        // The ticks must be looked up using the decoded index, even with tagged IDs
+        vec.advance_tick();
+        *vec.get_mut(ids[2]).unwrap() += 1;
+        let changed = vec.iter_changed_since(tick).collect::<Vec<_>>();
+        assert_eq!(changed, vec![(ids[2], &3)]);
+        let added = vec.iter_added_since(tick - 1).collect::<Vec<_>>();
+        assert_eq!(added, vec![(id, &10)]);
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn stable_codec_test() {
+        use crate::utils::IdCodec;
+        let mut vec = StableOrderedVec::<u32>::with_codec(IdCodec::LARGE);
+        let ids = (0..3).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        assert_eq!(vec.codec().decode(ids[2]), (2, 0));
+        vec.remove(ids[1]);
+        let id = vec.push_shove(10);
+        assert_eq!(vec.codec().decode(id), (1, 1));
+        assert_eq!(vec.get(ids[1]), None);
+        assert_eq!(vec.get(Id::NULL), None);
+
+        // Defragmenting keeps the encoded IDs valid
+        vec.remove(ids[0]);
+        vec.defragment();
+        assert_eq!(vec[id], 10);
+        assert_eq!(vec[ids[2]], 2);
+        assert!(vec.iter().any(|(x, val)| x == id && *val == 10));
+        assert_eq!(vec.validate(), Ok(()));
+    }
+    #[test]
+    pub fn iter_filtered_empty_mask_test() {
+        let mut vec = OrderedVec::<u32>::default();
+        let ids = (0..4).map(|x| vec.push_shove(x)).collect::<Vec<_>>();
+        vec.set_tag(ids[1], 3);
+        vec.remove(ids[2]);
+        // The empty mask has no tag constraint, even for the elements that never had any tags
+        let all = vec.iter_filtered(0).collect::<Vec<_>>();
+        assert_eq!(all, vec.iter().collect::<Vec<_>>());
+        assert_eq!(all.len(), 3);
+        assert_eq!(OrderedVec::<u32>::default().iter_filtered(0).count(), 0);
     }
 }
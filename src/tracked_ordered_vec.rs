@@ -0,0 +1,214 @@
+use std::{fmt::Debug, ops::Index};
+
+use crate::{
+    invariant::{check_len, InvariantError},
+    metrics::Metrics,
+    slots::Slots,
+    utils::{Id, IdCodec},
+};
+
+/// The ticks at which an element was added and last changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ticks {
+    /// The tick at which the element was added
+    pub added: u32,
+    /// The tick at which the element was last accessed mutably
+    pub changed: u32,
+}
+
+/// An ordered vector that keeps track of when each element was added and changed
+/// Whenever we add an element or fetch it mutably, its slot gets stamped with the current tick, so we can later only iterate over the elements that changed
+pub struct TrackedOrderedVec<T> {
+    /// The slots containing the elements and their versions
+    pub(crate) slots: Slots<T, u32>,
+    /// The ticks of each slot
+    pub(crate) ticks: Vec<Ticks>,
+    /// The current tick
+    pub(crate) tick: u32,
+    /// The codec used to pack the index and version of each element into an ID
+    pub(crate) codec: IdCodec,
+}
+
+impl<T> Clone for TrackedOrderedVec<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            ticks: self.ticks.clone(),
+            tick: self.tick,
+            codec: self.codec,
+        }
+    }
+}
+
+impl<T> Debug for TrackedOrderedVec<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrackedOrderedVec")
+            .field("vec", &self.slots.vec)
+            .field("missing", &self.slots.missing)
+            .field("ticks", &self.ticks)
+            .field("tick", &self.tick)
+            .finish()
+    }
+}
+
+impl<T> Default for TrackedOrderedVec<T> {
+    fn default() -> Self {
+        Self {
+            slots: Slots::default(),
+            ticks: Vec::new(),
+            tick: 0,
+            codec: IdCodec::default(),
+        }
+    }
+}
+
+/// Actual code
+impl<T> TrackedOrderedVec<T> {
+    /// New
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new ordered vector that packs its IDs using a specific codec
+    pub fn with_codec(codec: IdCodec) -> Self {
+        Self {
+            codec: codec.salted(),
+            ..Self::default()
+        }
+    }
+    /// Get the codec used to pack the IDs of this ordered vector
+    pub fn codec(&self) -> IdCodec {
+        self.codec
+    }
+    /// Get the current tick
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+    /// Advance to the next tick and return it. The changes made from now on will be stamped with this new tick
+    pub fn advance_tick(&mut self) -> u32 {
+        self.tick = self.tick.wrapping_add(1);
+        self.tick
+    }
+    /// Add an element to the ordered vector
+    pub fn push_shove(&mut self, elem: T) -> Id {
+        let (index, version) = self.slots.push_shove(elem);
+        let ticks = Ticks {
+            added: self.tick,
+            changed: self.tick,
+        };
+        if index == self.ticks.len() {
+            self.ticks.push(ticks);
+        } else {
+            self.ticks[index] = ticks;
+        }
+        // Wrap the version around so it fits inside the ID
+        let version = version & self.codec.version_mask();
+        self.slots.vec[index].1 = version;
+        self.codec.encode(index, version)
+    }
+    /// Overwrite an element that is contained in the vec, and return the old one
+    pub fn insert(&mut self, id: Id, elem: T) -> Option<T> {
+        let old = self.get_mut(id)?;
+        Some(std::mem::replace(old, elem))
+    }
+    /// Get the ID of the next element that we will add
+    pub fn get_next_id(&self) -> Id {
+        let (index, version) = self.slots.next_slot();
+        self.codec
+            .encode(index, version & self.codec.version_mask())
+    }
+    /// Remove an element that is contained in the vec
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.remove(index, version)
+    }
+    /// Get a reference to an element in the ordered vector
+    pub fn get(&self, id: Id) -> Option<&T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)
+    }
+    /// Get a mutable reference to an element in the ordered vector. This marks the element as changed
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        let elem = self.slots.get_mut(index, version)?;
+        self.ticks[index].changed = self.tick;
+        Some(elem)
+    }
+    /// Get the ticks of an element in the ordered vector
+    pub fn ticks(&self, id: Id) -> Option<Ticks> {
+        let (index, version) = self.codec.checked_decode(id)?;
+        self.slots.get(index, version)?;
+        Some(self.ticks[index])
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()?;
+        check_len("ticks", self.slots.vec.len(), self.ticks.len())
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.slots.metrics();
+        metrics.bytes_allocated += self.ticks.capacity() * std::mem::size_of::<Ticks>();
+        metrics
+    }
+    /// Get the number of valid elements in the ordered vector
+    pub fn count(&self) -> usize {
+        self.slots.count()
+    }
+    /// Get the number of invalid elements in the ordered vector
+    pub fn count_invalid(&self) -> usize {
+        self.slots.count_invalid()
+    }
+    /// Clear the whole ordered vector. This keeps the current tick
+    pub fn clear(&mut self) -> Vec<Option<T>> {
+        self.ticks.clear();
+        self.slots.clear()
+    }
+}
+
+/// Iter magic
+impl<T> TrackedOrderedVec<T> {
+    /// Get an iterator over the valid elements, but with the ID of each element
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+    /// Get a mutable iterator over the valid elements, but with the ID of each element. This marks every element as changed
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Id, &mut T)> {
+        let tick = self.tick;
+        let codec = self.codec;
+        let ticks = &mut self.ticks;
+        self.slots.iter_mut().map(move |(index, version, val)| {
+            ticks[index].changed = tick;
+            (codec.encode(index, version), val)
+        })
+    }
+    /// Get an iterator over the elements that were added or changed after a specific tick
+    pub fn iter_changed_since(&self, tick: u32) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .filter(move |(index, _, _)| self.ticks[*index].changed > tick)
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+    /// Get an iterator over the elements that were added after a specific tick
+    pub fn iter_added_since(&self, tick: u32) -> impl Iterator<Item = (Id, &T)> {
+        self.slots
+            .iter()
+            .filter(move |(index, _, _)| self.ticks[*index].added > tick)
+            .map(|(index, version, val)| (self.codec.encode(index, version), val))
+    }
+}
+
+/// Traits
+impl<T> Index<Id> for TrackedOrderedVec<T> {
+    type Output = T;
+    fn index(&self, index: Id) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use crate::{ordered_vec::OrderedVec, utils::Id};
+
+// A buffered edit
+enum Edit<T> {
+    Insert(T),
+    Remove(Id),
+    Update(Id, T),
+}
+
+/// A batch of edits on an OrderedVec that only get applied when we commit them
+/// Dropping the transaction without committing it discards all of its edits, so the ordered vector never ends up half edited
+pub struct Transaction<'a, T> {
+    /// The ordered vector that we will edit
+    pub(crate) vec: &'a mut OrderedVec<T>,
+    /// The buffered edits, in the order they were made
+    edits: Vec<Edit<T>>,
+    /// The number of buffered inserts
+    inserts: usize,
+}
+
+impl<'a, T> Transaction<'a, T> {
+    // Start an empty transaction
+    pub(crate) fn new(vec: &'a mut OrderedVec<T>) -> Self {
+        Self {
+            vec,
+            edits: Vec::new(),
+            inserts: 0,
+        }
+    }
+    /// Buffer an insert. The element only gets its ID on commit, so this returns the position of its ID inside the IDs returned by commit()
+    pub fn insert(&mut self, elem: T) -> usize {
+        self.edits.push(Edit::Insert(elem));
+        self.inserts += 1;
+        self.inserts - 1
+    }
+    /// Buffer the removal of an element
+    pub fn remove(&mut self, id: Id) {
+        self.edits.push(Edit::Remove(id));
+    }
+    /// Buffer the replacement of an element
+    pub fn update(&mut self, id: Id, elem: T) {
+        self.edits.push(Edit::Update(id, elem));
+    }
+    /// Get a reference to an element as it was before the transaction
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.vec.get(id)
+    }
+    /// Get the number of buffered edits
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+    /// Check if there are no buffered edits
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+    /// Discard all the buffered edits. This is the same as dropping the transaction
+    pub fn rollback(self) {}
+    /// Apply all the buffered edits in order, and return the IDs of the inserted elements
+    /// If one of the removes or updates targets an element that doesn't exist (or that got removed earlier in the transaction), nothing gets applied and we return None
+    pub fn commit(self) -> Option<Vec<Id>> {
+        // Validate everything before touching the ordered vector
+        let mut removed = HashSet::new();
+        for edit in self.edits.iter() {
+            let valid = match edit {
+                Edit::Insert(_) => true,
+                Edit::Remove(id) => self.vec.get(*id).is_some() && removed.insert(*id),
+                Edit::Update(id, _) => self.vec.get(*id).is_some() && !removed.contains(id),
+            };
+            if !valid {
+                return None;
+            }
+        }
+        // Now we can't fail anymore
+        let mut ids = Vec::with_capacity(self.inserts);
+        for edit in self.edits {
+            match edit {
+                Edit::Insert(elem) => ids.push(self.vec.push_shove(elem)),
+                Edit::Remove(id) => {
+                    self.vec.remove(id);
+                }
+                Edit::Update(id, elem) => *self.vec.get_mut(id).unwrap() = elem,
+            }
+        }
+        Some(ids)
+    }
+}
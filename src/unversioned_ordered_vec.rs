@@ -1,14 +1,20 @@
 use std::{
     fmt::Debug,
     ops::{Index, IndexMut},
+    sync::Arc,
+};
+
+use crate::{
+    invariant::InvariantError,
+    metrics::Metrics,
+    slots::Slots,
+    utils::{GrowthPolicy, ReusePolicy},
 };
 
 /// A collection that keeps the ordering of its elements, even when deleting an element
 pub struct UnversionnedOrderedVec<T> {
-    /// A list of the current elements in the list
-    pub(crate) vec: Vec<Option<T>>,
-    /// A list of the indices that contain a null element, so whenever we add a new element, we will add it there
-    pub(crate) missing: Vec<usize>,
+    /// The slots containing the elements
+    pub(crate) slots: Slots<T, ()>,
 }
 
 impl<T> Clone for UnversionnedOrderedVec<T>
@@ -17,8 +23,7 @@ where
 {
     fn clone(&self) -> Self {
         Self {
-            vec: self.vec.clone(),
-            missing: self.missing.clone(),
+            slots: self.slots.clone(),
         }
     }
 }
@@ -29,8 +34,8 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("UnversionnedOrderedVec")
-            .field("vec", &self.vec)
-            .field("missing", &self.missing)
+            .field("vec", &self.slots.vec)
+            .field("missing", &self.slots.missing)
             .finish()
     }
 }
@@ -38,8 +43,7 @@ where
 impl<T> Default for UnversionnedOrderedVec<T> {
     fn default() -> Self {
         Self {
-            vec: Vec::new(),
-            missing: Vec::new(),
+            slots: Slots::default(),
         }
     }
 }
@@ -50,125 +54,106 @@ impl<T> UnversionnedOrderedVec<T> {
     pub fn new() -> Self {
         Self::default()
     }
+    /// Create a new ordered vector that re-uses its free slots using a specific policy
+    pub fn with_reuse_policy(policy: ReusePolicy) -> Self {
+        Self {
+            slots: Slots::with_policy(policy),
+        }
+    }
+    /// Create a new ordered vector that grows its slots using a specific growth policy
+    pub fn with_growth_policy(policy: impl GrowthPolicy + 'static) -> Self {
+        let mut vec = Self::default();
+        vec.slots.growth = Some(Arc::new(policy));
+        vec
+    }
     /// Create Self using already existing elements
     pub fn from_valids(vals: Vec<T>) -> Self {
         Self {
-            vec: vals
-                .into_iter()
-                .map(|x| Some(x))
-                .collect::<Vec<Option<T>>>(),
-            missing: Vec::new(),
+            slots: Slots::from_valids(vals),
         }
     }
+    /// Get the policy used to re-use the free slots
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        self.slots.policy
+    }
     /// Add an element to the ordered vector
     pub fn push_shove(&mut self, elem: T) -> usize {
-        if self.missing.is_empty() {
-            // Add the element normally
-            self.vec.push(Some(elem));
-            self.vec.len() - 1
-        } else {
-            // If we have some null elements, we can validate the given element there
-            let index = self.missing.pop().unwrap();
-            let old_val = self.vec.get_mut(index as usize).unwrap();
-            *old_val = Some(elem);
-            index
-        }
+        self.slots.push_shove(elem).0
     }
     /// Get the index of the next element that we will add
     pub fn get_next_idx(&self) -> usize {
-        // Normal push
-        if self.missing.is_empty() {
-            return self.vec.len();
-        }
-        // Shove
-        *self.missing.last().unwrap()
+        self.slots.next_slot().0
     }
     /// Remove an element that is contained in the vec
     pub fn remove(&mut self, index: usize) -> Option<T> {
-        self.missing.push(index);
-        let elem = self.vec.get_mut(index)?;
-        std::mem::take(elem)
+        self.slots.remove_index(index)
     }
     /// Get a reference to an element in the ordered vector
     pub fn get(&self, index: usize) -> Option<&T> {
-        self.vec.get(index)?.as_ref()
+        self.slots.get(index, 0)
     }
     /// Get a mutable reference to an element in the ordered vector
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        self.vec.get_mut(index)?.as_mut()
+        self.slots.get_mut(index, 0)
+    }
+    /// Check the structural invariants of the ordered vector, like the free list matching up with the empty slots
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        self.slots.validate()
+    }
+    /// Get the memory and fragmentation statistics of the ordered vector
+    pub fn metrics(&self) -> Metrics {
+        self.slots.metrics()
     }
     /// Get the number of valid elements in the ordered vector
     pub fn count(&self) -> usize {
-        self.vec.len() - self.missing.len()
+        self.slots.count()
     }
     /// Get the number of invalid elements in the ordered vector
     pub fn count_invalid(&self) -> usize {
-        self.missing.len()
+        self.slots.count_invalid()
     }
     /// Clear the whole ordered vector
     pub fn clear(&mut self) -> Vec<Option<T>> {
-        // Simple clear
-        let rep = std::mem::take(&mut self.vec);
-        self.missing.clear();
-        rep.into_iter().collect::<Vec<_>>()
+        self.slots.clear()
     }
 }
 
 /// Iter magic
 impl<T> UnversionnedOrderedVec<T> {
     /// Convert this into an iterator
+    #[allow(clippy::should_implement_trait)]
     pub fn into_iter(self) -> impl Iterator<Item = (usize, T)> {
-        self.vec
-            .into_iter()
-            .enumerate()
-            .filter_map(|(index, val)| val.map(|val| (index, val)))
+        self.slots.into_iter().map(|(index, _, val)| (index, val))
     }
     /// Get an iterator over the valid elements
     pub fn iter_elements(&self) -> impl Iterator<Item = &T> {
-        self.vec.iter().filter_map(|val| val.as_ref())
+        self.slots.iter_elements()
     }
     /// Get a mutable iterator over the valid elements
     pub fn iter_elements_mut(&mut self) -> impl Iterator<Item = &mut T> {
-        self.vec.iter_mut().filter_map(|val| val.as_mut())
+        self.slots.iter_elements_mut()
     }
     /// Get an iterator over the valid elements, but with the ID of each element
     pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
-        self.vec
-            .iter()
-            .enumerate()
-            .filter_map(|(index, val)| val.as_ref().map(|val| (index, val)))
+        self.slots.iter().map(|(index, _, val)| (index, val))
     }
     /// Get a mutable iterator over the valid elements, but with the ID of each element
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
-        self.vec
-            .iter_mut()
-            .enumerate()
-            .filter_map(|(index, val)| val.as_mut().map(|val| (index, val)))
+        self.slots.iter_mut().map(|(index, _, val)| (index, val))
     }
     /// Get an iterator over the indices of the null elements
     pub fn iter_invalid(&self) -> impl Iterator<Item = &usize> {
-        self.missing.iter()
+        self.slots.missing.iter()
     }
     /// Drain the elements that only return true. This will return just an Iterator of the index and value of the drained elements
-    pub fn my_drain<F>(&mut self, mut filter: F) -> impl Iterator<Item = (usize, T)> + '_
+    /// The elements are removed lazily, so dropping the iterator early leaves the remaining elements untouched
+    pub fn my_drain<'a, F>(&'a mut self, mut filter: F) -> impl Iterator<Item = (usize, T)> + 'a
     where
-        F: FnMut(usize, &T) -> bool,
+        F: FnMut(usize, &T) -> bool + 'a,
     {
-        // Keep track of the indices that we must remove
-        let mut removed_indices: Vec<usize> = Vec::new();
-        for (index, val) in self.vec.iter_mut().enumerate() {
-            if let Some(val) = val {
-                // If it validates the filter, we must remove it
-                if filter(index, val) {
-                    // We must remove this value
-                    removed_indices.push(index);
-                }
-            }
-        }
-        // Now we can actually remove the objects
-        removed_indices
-            .into_iter()
-            .map(|id| (id, self.remove(id).unwrap()))
+        self.slots
+            .drain_filter(move |index, _, val| filter(index, val))
+            .map(|(index, _, val)| (index, val))
     }
 }
 
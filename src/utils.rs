@@ -1,5 +1,12 @@
+use std::{
+    fmt::{Debug, Display},
+    panic::RefUnwindSafe,
+    str::FromStr,
+    sync::atomic::{AtomicU32, Ordering::Relaxed},
+};
+
 // An index pair containing the actual index and the version
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct IndexPair {
     // First 32 bits
     pub index: u32,
@@ -9,26 +16,552 @@ pub struct IndexPair {
 }
 
 impl IndexPair {
-    // New
-    pub fn new(index: usize, version: u32) -> Self {
+    // New. The index must fit in 32 bits
+    pub const fn new(index: usize, version: u32) -> Self {
+        debug_assert!(index <= u32::MAX as usize, "Index does not fit in 32 bits");
         Self {
             index: index as u32,
             version,
         }
     }
+    // New, but return None if the index does not fit in 32 bits instead of truncating it
+    pub const fn try_new(index: usize, version: u32) -> Option<Self> {
+        if index > u32::MAX as usize {
+            return None;
+        }
+        Some(Self {
+            index: index as u32,
+            version,
+        })
+    }
 }
 
-// Convert an index and version to a u64 ID
-pub fn to_id(pair: IndexPair) -> u64 {
-    // We do the bit shifting magic
-    let mut id = pair.index as u64;
-    id |= (pair.version as u64) << 32;
-    id
+// Convert an index and version to a u64 ID, using the default 32/32 layout
+pub const fn to_id(pair: IndexPair) -> u64 {
+    IdCodec::DEFAULT.pack(pair.index as u64, pair.version)
 }
-// Convert a u64 ID to an index and version
-pub fn from_id(id: u64) -> IndexPair {
-    // We do the bit shifting magic
-    let index = ((id << 32) >> 32) as u32;
-    let version = (id >> 32) as u32;
+// Convert a u64 ID to an index and version, using the default 32/32 layout
+pub const fn from_id(id: u64) -> IndexPair {
+    let (index, version) = IdCodec::DEFAULT.unpack(id);
+    IndexPair {
+        index: index as u32,
+        version,
+    }
+}
+
+/// Decides how much capacity the slots of a collection get when they must grow
+/// Without a growth policy, the collections let their Vec double its capacity
+/// Policies get shared between the clones of a collection, so they must be thread safe
+pub trait GrowthPolicy: Send + Sync + RefUnwindSafe {
+    /// Get the capacity that we must grow to, given the current capacity and the number of slots that we need. Anything smaller than `needed` gets rounded up to it
+    fn next_capacity(&self, current: usize, needed: usize) -> usize;
+}
+
+impl<F: Fn(usize, usize) -> usize + Send + Sync + RefUnwindSafe> GrowthPolicy for F {
+    fn next_capacity(&self, current: usize, needed: usize) -> usize {
+        self(current, needed)
+    }
+}
+
+/// Double the capacity every time, just like a Vec does
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    fn next_capacity(&self, current: usize, needed: usize) -> usize {
+        current.saturating_mul(2).max(needed)
+    }
+}
+
+/// Grow the capacity by half of itself every time, which wastes less memory than doubling
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct OneAndAHalf;
+
+impl GrowthPolicy for OneAndAHalf {
+    fn next_capacity(&self, current: usize, needed: usize) -> usize {
+        current.saturating_add(current / 2).max(needed)
+    }
+}
+
+/// Grow the capacity by a fixed number of slots every time
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Increment(pub usize);
+
+impl GrowthPolicy for Increment {
+    fn next_capacity(&self, current: usize, needed: usize) -> usize {
+        current.saturating_add(self.0).max(needed)
+    }
+}
+
+/// Decides which free slot gets re-used when we add a new element
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ReusePolicy {
+    /// Re-use the slot that was freed last. This is the fastest one
+    #[default]
+    Lifo,
+    /// Re-use the slot that was freed first, which maximizes the time before an index gets recycled, so stale IDs are less likely to collide
+    Fifo,
+    /// Re-use the free slot with the lowest index, which keeps the elements packed at the start
+    LowestIndex,
+}
+
+/// Describes how the index (lower bits), the version, and the optional collection tag (upper bits) are packed inside a u64 ID
+/// This is the only place that knows about the layout of a u64 ID, everything else must go through it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdCodec {
+    /// How many of the lower bits are used for the index
+    index_bits: u32,
+    /// How many of the upper bits are used for the collection tag
+    tag_bits: u32,
+    /// The tag of the collection that uses this codec
+    tag: u32,
+}
+
+impl Default for IdCodec {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+// The next tag that we will give to a collection
+static NEXT_TAG: AtomicU32 = AtomicU32::new(1);
+
+impl IdCodec {
+    /// 32 bits of index and 32 bits of version
+    pub const DEFAULT: Self = Self::new(32);
+    /// 48 bits of index and 16 bits of version, for huge collections
+    pub const LARGE: Self = Self::new(48);
+    /// Create a codec that uses `index_bits` bits for the index, and the rest (up to 32 bits) for the version
+    pub const fn new(index_bits: u32) -> Self {
+        assert!(
+            index_bits > 0 && index_bits < 64,
+            "The index must use between 1 and 63 bits"
+        );
+        Self {
+            index_bits,
+            tag_bits: 0,
+            tag: 0,
+        }
+    }
+    /// Reserve the upper `tag_bits` bits of each ID for a collection tag, taking them away from the version
+    /// Each collection that gets created using this codec will receive its own tag, so we can detect IDs that get passed to the wrong collection
+    pub const fn with_tag_bits(self, tag_bits: u32) -> Self {
+        assert!(
+            tag_bits <= 32 && self.index_bits + tag_bits < 64,
+            "The version must keep at least 1 bit"
+        );
+        Self {
+            index_bits: self.index_bits,
+            tag_bits,
+            tag: 0,
+        }
+    }
+    /// Give a fresh tag to this codec. This gets called by the collections when they get created
+    pub fn salted(self) -> Self {
+        if self.tag_bits == 0 {
+            return self;
+        }
+        // Skip the 0 tag, since untagged IDs use it
+        let mask = u32::MAX >> (32 - self.tag_bits);
+        let tag = loop {
+            let tag = NEXT_TAG.fetch_add(1, Relaxed) & mask;
+            if tag != 0 {
+                break tag;
+            }
+        };
+        Self { tag, ..self }
+    }
+    // Use a specific tag, when restoring a codec that was saved somewhere
+    pub(crate) const fn with_tag(self, tag: u32) -> Self {
+        Self {
+            index_bits: self.index_bits,
+            tag_bits: self.tag_bits,
+            tag,
+        }
+    }
+    /// Get the number of bits used for the index
+    pub const fn index_bits(&self) -> u32 {
+        self.index_bits
+    }
+    /// Get the number of bits used for the version
+    pub const fn version_bits(&self) -> u32 {
+        let bits = 64 - self.index_bits - self.tag_bits;
+        if bits > 32 {
+            32
+        } else {
+            bits
+        }
+    }
+    /// Get the number of bits used for the collection tag
+    pub const fn tag_bits(&self) -> u32 {
+        self.tag_bits
+    }
+    /// Get the tag that gets stored inside each ID
+    pub const fn tag(&self) -> u32 {
+        self.tag
+    }
+    /// Get the biggest index that can be stored
+    pub const fn max_index(&self) -> u64 {
+        u64::MAX >> (64 - self.index_bits)
+    }
+    /// Get the mask that must be applied to versions so they wrap around
+    pub const fn version_mask(&self) -> u32 {
+        u32::MAX >> (32 - self.version_bits())
+    }
+    /// Pack an index and a version into a raw u64 ID
+    pub const fn pack(&self, index: u64, version: u32) -> u64 {
+        // Widen before shifting, otherwise the version would get shifted out of a u32
+        let mut id = ((version & self.version_mask()) as u64) << self.index_bits
+            | (index & self.max_index());
+        if self.tag_bits > 0 {
+            id |= (self.tag as u64) << (64 - self.tag_bits);
+        }
+        id
+    }
+    /// Unpack a raw u64 ID into its index and version. This ignores the tag
+    pub const fn unpack(&self, id: u64) -> (u64, u32) {
+        let index = id & self.max_index();
+        let version = (id >> self.index_bits) as u32 & self.version_mask();
+        (index, version)
+    }
+    /// Get the tag stored inside an ID
+    pub const fn tag_of(&self, id: Id) -> u32 {
+        if self.tag_bits == 0 {
+            0
+        } else {
+            (id.0 >> (64 - self.tag_bits)) as u32
+        }
+    }
+    /// Check if an ID was created by a collection that uses this codec
+    pub const fn owns(&self, id: Id) -> bool {
+        self.tag_of(id) == self.tag
+    }
+    /// Pack an index and a version into an ID
+    pub const fn encode(&self, index: usize, version: u32) -> Id {
+        debug_assert!(
+            index as u64 <= self.max_index(),
+            "Index is too big for this codec"
+        );
+        Id(self.pack(index as u64, version))
+    }
+    /// Pack an index and a version into an ID, but return None if the index is too big for this codec instead of wrapping it around
+    /// Collections with more than 4 billion slots must use a wider codec, like [`IdCodec::LARGE`]
+    pub const fn try_encode(&self, index: usize, version: u32) -> Option<Id> {
+        if index as u64 > self.max_index() {
+            return None;
+        }
+        Some(self.encode(index, version))
+    }
+    /// Unpack an ID into its index and version, but only if it is not null and was created by a collection that uses this codec
+    /// IDs that come from another collection will panic when debug assertions are enabled
+    pub fn checked_decode(&self, id: Id) -> Option<(usize, u32)> {
+        // The null ID never points to anything
+        if id.is_null() {
+            return None;
+        }
+        if !self.owns(id) {
+            if cfg!(debug_assertions) {
+                panic!("{:?} does not belong to this collection", id);
+            }
+            return None;
+        }
+        Some(self.decode(id))
+    }
+    /// Unpack an ID into its index and version
+    pub const fn decode(&self, id: Id) -> (usize, u32) {
+        let (index, version) = self.unpack(id.0);
+        (index as usize, version)
+    }
+}
+// Convert an index and version to a compact u32 ID, with a 24 bit index and an 8 bit version
+pub const fn to_id32(pair: IndexPair) -> u32 {
+    // We only keep the lower bits of each
+    (pair.index & 0xFF_FFFF) | ((pair.version & 0xFF) << 24)
+}
+// Convert a compact u32 ID to an index and version
+pub const fn from_id32(id: u32) -> IndexPair {
+    let index = id & 0xFF_FFFF;
+    let version = id >> 24;
     IndexPair { index, version }
 }
+
+/// A packed ID containing the index (lower 32 bits) and the version (upper 32 bits) of an element
+/// Collections that use another [`IdCodec`] must be queried using [`IdCodec::decode`] instead of the accessors
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct Id(u64);
+
+impl Id {
+    /// A reserved ID that never points to an element
+    pub const NULL: Id = Id(u64::MAX);
+    /// Create the null ID
+    pub const fn null() -> Self {
+        Self::NULL
+    }
+    /// Check if this is the null ID
+    pub const fn is_null(&self) -> bool {
+        self.0 == Self::NULL.0
+    }
+    /// Create an ID from an index and a version
+    pub const fn new(index: usize, version: u32) -> Self {
+        Self(to_id(IndexPair::new(index, version)))
+    }
+    /// Create an ID from an index and a version, but return None if the index does not fit in 32 bits
+    pub const fn try_new(index: usize, version: u32) -> Option<Self> {
+        match IndexPair::try_new(index, version) {
+            Some(pair) => Some(Self(to_id(pair))),
+            None => None,
+        }
+    }
+    /// Get the index of the element
+    pub const fn index(&self) -> usize {
+        from_id(self.0).index as usize
+    }
+    /// Get the version of the element
+    pub const fn version(&self) -> u32 {
+        from_id(self.0).version
+    }
+    /// Get the packed u64 representation of this ID
+    pub const fn to_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl Debug for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_null() {
+            write!(f, "Id(null)")
+        } else {
+            write!(f, "Id(index={}, version={})", self.index(), self.version())
+        }
+    }
+}
+
+impl Display for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_null() {
+            write!(f, "null")
+        } else {
+            write!(f, "{}:{}", self.index(), self.version())
+        }
+    }
+}
+
+impl FromStr for Id {
+    type Err = ParseIdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "null" {
+            return Ok(Self::NULL);
+        }
+        let (index, version) = parse_index_version(s)?;
+        let index = u32::try_from(index).map_err(|_| ParseIdError(s.to_string()))?;
+        Ok(Self::new(index as usize, version))
+    }
+}
+
+// Conversions
+impl From<u64> for Id {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<Id> for u64 {
+    fn from(id: Id) -> Self {
+        id.0
+    }
+}
+
+impl From<IndexPair> for Id {
+    fn from(pair: IndexPair) -> Self {
+        Self(to_id(pair))
+    }
+}
+
+impl From<Id> for IndexPair {
+    fn from(id: Id) -> Self {
+        from_id(id.0)
+    }
+}
+
+/// A compact ID containing the index (lower 24 bits) and the version (upper 8 bits) of an element
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct Id32(u32);
+
+impl Id32 {
+    /// A reserved ID that never points to an element
+    pub const NULL: Id32 = Id32(u32::MAX);
+    /// The maximum number of slots that can be addressed using a compact ID. The last index is reserved for the null ID
+    pub const MAX_INDEX: usize = (1 << 24) - 1;
+    /// Create the null ID
+    pub const fn null() -> Self {
+        Self::NULL
+    }
+    /// Check if this is the null ID
+    pub const fn is_null(&self) -> bool {
+        self.0 == Self::NULL.0
+    }
+    /// Create an ID from an index and a version. The index must fit in 24 bits
+    pub const fn new(index: usize, version: u8) -> Self {
+        assert!(index < Self::MAX_INDEX, "Index does not fit in an Id32");
+        Self(to_id32(IndexPair::new(index, version as u32)))
+    }
+    /// Get the index of the element
+    pub const fn index(&self) -> usize {
+        from_id32(self.0).index as usize
+    }
+    /// Get the version of the element
+    pub const fn version(&self) -> u8 {
+        from_id32(self.0).version as u8
+    }
+    /// Get the packed u32 representation of this ID
+    pub const fn to_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl Debug for Id32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_null() {
+            write!(f, "Id32(null)")
+        } else {
+            write!(
+                f,
+                "Id32(index={}, version={})",
+                self.index(),
+                self.version()
+            )
+        }
+    }
+}
+
+impl Display for Id32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_null() {
+            write!(f, "null")
+        } else {
+            write!(f, "{}:{}", self.index(), self.version())
+        }
+    }
+}
+
+impl FromStr for Id32 {
+    type Err = ParseIdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "null" {
+            return Ok(Self::NULL);
+        }
+        let (index, version) = parse_index_version(s)?;
+        let version = u8::try_from(version).map_err(|_| ParseIdError(s.to_string()))?;
+        if index >= Self::MAX_INDEX as u64 {
+            return Err(ParseIdError(s.to_string()));
+        }
+        Ok(Self::new(index as usize, version))
+    }
+}
+
+// Conversions
+impl From<u32> for Id32 {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+impl From<Id32> for u32 {
+    fn from(id: Id32) -> Self {
+        id.0
+    }
+}
+
+/// The error returned when we fail to parse an ID from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIdError(String);
+
+impl Display for ParseIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' is not a valid ID, expected 'index:version'",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseIdError {}
+
+// Parse an "index:version" string
+fn parse_index_version(s: &str) -> Result<(u64, u32), ParseIdError> {
+    let err = || ParseIdError(s.to_string());
+    let (index, version) = s.trim().split_once(':').ok_or_else(err)?;
+    let index = index.trim().parse::<u64>().map_err(|_| err())?;
+    let version = version.trim().parse::<u32>().map_err(|_| err())?;
+    Ok((index, version))
+}
+
+/// An ID that remembers the type of the element it points to, so it can't be used with an ordered vector of another type
+#[cfg(feature = "typed-handles")]
+#[repr(transparent)]
+pub struct Handle<T> {
+    /// The untyped ID
+    id: Id,
+    /// Use a function pointer so the handle is always Send and Sync
+    _phantom: std::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "typed-handles")]
+impl<T> Handle<T> {
+    /// Create a typed handle from an untyped ID. Nothing checks that the ID actually points to an element of type T
+    pub const fn from_id(id: Id) -> Self {
+        Self {
+            id,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+    /// Get the untyped ID of this handle
+    pub const fn id(&self) -> Id {
+        self.id
+    }
+}
+
+// We implement these manually since deriving them would require T to implement them as well
+#[cfg(feature = "typed-handles")]
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "typed-handles")]
+impl<T> Copy for Handle<T> {}
+
+#[cfg(feature = "typed-handles")]
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+#[cfg(feature = "typed-handles")]
+impl<T> Eq for Handle<T> {}
+
+#[cfg(feature = "typed-handles")]
+impl<T> std::hash::Hash for Handle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+#[cfg(feature = "typed-handles")]
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle<{}>({})", std::any::type_name::<T>(), self.id)
+    }
+}
+
+#[cfg(feature = "typed-handles")]
+impl<T> From<Handle<T>> for Id {
+    fn from(handle: Handle<T>) -> Self {
+        handle.id
+    }
+}